@@ -0,0 +1,345 @@
+// Expected values used to learn and advise on discards
+use crate::cardset::CardSet;
+use crate::game::{Card, Hand, Rank, Suit};
+use crate::score::score_hand;
+use itertools::Itertools;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+// Show scores for a kept hand over every starter that could still be cut
+#[derive(Clone, Debug)]
+pub struct StarterStats {
+    pub expected: f64,
+    pub min: u8,
+    pub max: u8,
+    pub distribution: Vec<usize>, // Number of starters giving each score, indexed by score
+}
+
+impl StarterStats {
+    pub fn starters(&self) -> usize {
+        self.distribution.iter().sum()
+    }
+}
+
+// Scores the kept cards with each card that isn't kept or otherwise known, e.g. the discards
+pub fn starter_stats(kept: CardSet, known: CardSet, crib: bool) -> StarterStats {
+    let mut distribution = Vec::new();
+    let mut total = 0;
+
+    for starter in (kept | known).complement() {
+        let score = score_hand(kept, &starter, crib) as usize;
+
+        if score >= distribution.len() {
+            distribution.resize(score + 1, 0);
+        }
+
+        distribution[score] += 1;
+        total += score;
+    }
+
+    let starters: usize = distribution.iter().sum();
+
+    StarterStats {
+        expected: total as f64 / starters.max(1) as f64,
+        min: distribution
+            .iter()
+            .position(|count| *count > 0)
+            .unwrap_or(0) as u8,
+        max: distribution.len().saturating_sub(1) as u8,
+        distribution,
+    }
+}
+
+// Expected value of one way to discard from the dealt hand
+#[derive(Clone, Debug)]
+pub struct DiscardOption {
+    pub discard: Vec<Card>,
+    pub kept: Vec<Card>,
+    pub hand: f64, // Expected show score of the kept cards
+    pub crib: f64, // Expected score of the crib with these cards in it
+    pub net: f64,  // Hand plus crib when the crib is ours, hand minus crib otherwise
+}
+
+// Rates every way to discard from the hand, best first
+// `unknown` is how many cards the other players add to the crib
+pub fn rank_discards(
+    hand: &Hand,
+    num_discard: usize,
+    unknown: usize,
+    own_crib: bool,
+) -> Vec<DiscardOption> {
+    rank_discards_by(hand, num_discard, own_crib, |discard, kept| {
+        crib_expectation(discard, kept, unknown)
+    })
+}
+
+fn rank_discards_by<F>(
+    hand: &Hand,
+    num_discard: usize,
+    own_crib: bool,
+    crib_value: F,
+) -> Vec<DiscardOption>
+where
+    F: Fn(CardSet, CardSet) -> f64,
+{
+    let mut options: Vec<DiscardOption> = hand
+        .cards()
+        .iter()
+        .combinations(num_discard)
+        .map(|discard| {
+            let discard: CardSet = discard.into_iter().collect();
+            let kept = CardSet::from(hand) - discard;
+
+            let hand = starter_stats(kept, discard, false).expected;
+            let crib = crib_value(discard, kept);
+            let net = if own_crib { hand + crib } else { hand - crib };
+
+            DiscardOption {
+                discard: discard.iter().collect(),
+                kept: kept.iter().collect(),
+                hand,
+                crib,
+                net,
+            }
+        })
+        .collect();
+
+    options.sort_by(|a, b| b.net.total_cmp(&a.net));
+    options
+}
+
+// Averages the crib over every set of unknown cards the others could throw and every starter
+pub fn crib_expectation(discard: CardSet, known: CardSet, unknown: usize) -> f64 {
+    let remaining: Vec<Card> = (discard | known).complement().iter().collect();
+    let mut total = 0;
+    let mut count = 0;
+
+    for others in remaining.iter().combinations(unknown) {
+        let others: CardSet = others.into_iter().collect();
+        let crib = discard | others;
+
+        for starter in (crib | known).complement() {
+            total += score_hand(crib, &starter, true) as usize;
+            count += 1;
+        }
+    }
+
+    total as f64 / count.max(1) as f64
+}
+
+// Average crib value of every discard pair, for the dealer's own crib and the pone's throw
+// Pairs sit on the diagonal, suited cards above it and offsuit cards below it
+#[derive(Clone, Debug)]
+pub struct CribTable {
+    dealer: [[f64; 13]; 13],
+    pone: [[f64; 13]; 13],
+}
+
+impl CribTable {
+    // Bumped whenever the file format or the way tables are generated changes
+    pub const VERSION: u32 = 1;
+
+    pub fn new() -> CribTable {
+        CribTable {
+            dealer: [[0.0; 13]; 13],
+            pone: [[0.0; 13]; 13],
+        }
+    }
+
+    // Row and column in the table for the pair
+    pub fn position(a: &Card, b: &Card) -> (usize, usize) {
+        let (low, high) = if a.rank <= b.rank {
+            (a.rank as usize, b.rank as usize)
+        } else {
+            (b.rank as usize, a.rank as usize)
+        };
+
+        if a.suit == b.suit {
+            (low, high)
+        } else {
+            (high, low)
+        }
+    }
+
+    pub fn get(&self, a: &Card, b: &Card, own_crib: bool) -> f64 {
+        let (row, column) = CribTable::position(a, b);
+
+        if own_crib {
+            self.dealer[row][column]
+        } else {
+            self.pone[row][column]
+        }
+    }
+
+    pub fn set(&mut self, a: &Card, b: &Card, own_crib: bool, value: f64) {
+        let (row, column) = CribTable::position(a, b);
+
+        if own_crib {
+            self.dealer[row][column] = value;
+        } else {
+            self.pone[row][column] = value;
+        }
+    }
+
+    // One pair of cards for each cell in the table
+    pub fn keys() -> Vec<(Card, Card)> {
+        let mut keys = Vec::new();
+
+        for (i, low) in Rank::ALL.into_iter().enumerate() {
+            for high in Rank::ALL.into_iter().skip(i) {
+                let spade = |rank| Card {
+                    rank,
+                    suit: Suit::Spades,
+                };
+                let heart = |rank| Card {
+                    rank,
+                    suit: Suit::Hearts,
+                };
+
+                keys.push((spade(low), heart(high)));
+
+                if low != high {
+                    keys.push((spade(low), spade(high)));
+                }
+            }
+        }
+
+        keys
+    }
+
+    pub fn load(path: &Path) -> Result<CribTable, io::Error> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), io::Error> {
+        fs::write(path, self.to_string())
+    }
+}
+
+impl Default for CribTable {
+    fn default() -> CribTable {
+        CribTable::new()
+    }
+}
+
+// Header line with the version, then one line per cell like "A5s 5.62 4.91"
+impl fmt::Display for CribTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "crib-table v{}", CribTable::VERSION)?;
+        writeln!(f, "# pair dealer pone")?;
+
+        for (a, b) in CribTable::keys() {
+            writeln!(
+                f,
+                "{} {:.4} {:.4}",
+                table_key(&a, &b),
+                self.get(&a, &b, true),
+                self.get(&a, &b, false)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl FromStr for CribTable {
+    type Err = io::Error;
+
+    fn from_str(s: &str) -> Result<CribTable, io::Error> {
+        let mut lines = s
+            .lines()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+
+        let header = format!("crib-table v{}", CribTable::VERSION);
+
+        if lines.next() != Some(header.as_str()) {
+            return Err(table_error("Unsupported crib table version"));
+        }
+
+        let mut table = CribTable::new();
+        let mut filled = [[false; 13]; 13];
+
+        for line in lines {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+
+            let (a, b, dealer, pone) = match fields.as_slice() {
+                [key, dealer, pone] => {
+                    let (a, b) = parse_table_key(key)?;
+                    (a, b, dealer.parse(), pone.parse())
+                }
+                _ => return Err(table_error(line)),
+            };
+
+            let (row, column) = CribTable::position(&a, &b);
+            table.set(&a, &b, true, dealer.map_err(|_| table_error(line))?);
+            table.set(&a, &b, false, pone.map_err(|_| table_error(line))?);
+            filled[row][column] = true;
+        }
+
+        if !filled.iter().flatten().all(|filled| *filled) {
+            return Err(table_error("Crib table is missing pairs"));
+        }
+
+        Ok(table)
+    }
+}
+
+// Ranks from low to high, then 's' for suited or 'o' for offsuit unless they're a pair
+fn table_key(a: &Card, b: &Card) -> String {
+    let (low, high) = if a.rank <= b.rank { (a, b) } else { (b, a) };
+    let mut key = format!("{}{}", low.rank.to_net_name(), high.rank.to_net_name());
+
+    if low.rank != high.rank {
+        key.push(if low.suit == high.suit { 's' } else { 'o' });
+    }
+
+    key
+}
+
+fn parse_table_key(key: &str) -> Result<(Card, Card), io::Error> {
+    let chars: Vec<char> = key.chars().collect();
+
+    let (low, high, suited) = match chars.as_slice() {
+        [low, high] if low == high => (low, high, false),
+        [low, high, 's'] if low != high => (low, high, true),
+        [low, high, 'o'] if low != high => (low, high, false),
+        _ => return Err(table_error(key)),
+    };
+
+    let card = |rank: &char, suit| -> Result<Card, io::Error> {
+        Ok(Card {
+            rank: Rank::try_from(*rank)?,
+            suit,
+        })
+    };
+
+    let second_suit = if suited { Suit::Spades } else { Suit::Hearts };
+
+    Ok((card(low, Suit::Spades)?, card(high, second_suit)?))
+}
+
+fn table_error(message: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("Invalid crib table: {}", message),
+    )
+}
+
+// Like rank_discards, but looks up the crib value of two card discards in the table
+pub fn rank_discards_with_table(
+    hand: &Hand,
+    table: &CribTable,
+    own_crib: bool,
+) -> Vec<DiscardOption> {
+    rank_discards_by(hand, 2, own_crib, |discard, _| {
+        let (a, b) = discard
+            .iter()
+            .collect_tuple()
+            .expect("Discard isn't two cards");
+        table.get(&a, &b, own_crib)
+    })
+}
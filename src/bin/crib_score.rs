@@ -0,0 +1,119 @@
+use clap::Parser;
+use cribbage::game::{Card, Hand, ScoreBreakdown, ScoreKind};
+use itertools::Itertools;
+use std::io;
+use std::process;
+
+#[derive(Parser)]
+struct ScoreArgs {
+    /// 4 cards in the hand, or 3 for five card cribbage, then the magic card, e.g. 5H 5C 5S JD 5D
+    #[arg(required = true)]
+    cards: Vec<String>,
+    /// Score the hand as a crib, which only scores a flush when the magic card matches
+    #[arg(long)]
+    crib: bool,
+    /// Print the score as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+fn main() {
+    let args = ScoreArgs::parse();
+
+    if let Err(e) = crib_score(args) {
+        eprintln!("Error: {}", e);
+        process::exit(1);
+    }
+}
+
+fn crib_score(args: ScoreArgs) -> Result<(), io::Error> {
+    // Cards can be given as separate arguments or in one, split by spaces or commas
+    let mut cards = args
+        .cards
+        .iter()
+        .flat_map(|arg| arg.split(|c: char| c == ',' || c.is_whitespace()))
+        .filter(|name| !name.is_empty())
+        .map(|name| name.parse::<Card>())
+        .collect::<Result<Vec<Card>, _>>()?;
+
+    // Four cards in a hand, or three in five card cribbage
+    if cards.len() != 5 && cards.len() != 4 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "Expected 4 cards, or 3 for five card cribbage, followed by the magic card",
+        ));
+    }
+
+    if !cards.iter().all_unique() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "The same card was given twice",
+        ));
+    }
+
+    let magic = cards.pop().expect("No magic card");
+    let mut hand = Hand::from(cards, Some(magic));
+    hand.set_crib(args.crib);
+
+    let breakdown = hand.score();
+
+    if args.json {
+        println!("{}", to_json(&hand, &breakdown));
+    } else {
+        hand.pretty_print(false, true);
+
+        for event in breakdown.events() {
+            println!("{}", event);
+        }
+
+        println!("{} points", breakdown.total());
+    }
+
+    Ok(())
+}
+
+// Card names are plain ASCII so nothing needs escaping
+fn to_json(hand: &Hand, breakdown: &ScoreBreakdown) -> String {
+    let cards = |cards: &[Card]| {
+        cards
+            .iter()
+            .map(|card| format!("\"{}\"", card.to_net_name()))
+            .join(",")
+    };
+
+    let events = breakdown
+        .events()
+        .iter()
+        .map(|event| {
+            format!(
+                "{{\"kind\":\"{}\",\"cards\":[{}],\"points\":{}}}",
+                kind_name(event.kind),
+                cards(&event.cards),
+                event.points
+            )
+        })
+        .join(",");
+
+    format!(
+        "{{\"cards\":[{}],\"magic\":\"{}\",\"crib\":{},\"total\":{},\"breakdown\":[{}]}}",
+        cards(hand.cards()),
+        hand.magic().expect("No magic card").to_net_name(),
+        hand.is_crib(),
+        breakdown.total(),
+        events
+    )
+}
+
+fn kind_name(kind: ScoreKind) -> &'static str {
+    match kind {
+        ScoreKind::Fifteen => "fifteen",
+        ScoreKind::Pair => "pair",
+        ScoreKind::Run => "run",
+        ScoreKind::Flush => "flush",
+        ScoreKind::Nob => "nob",
+        ScoreKind::Heels => "heels",
+        ScoreKind::ThirtyOne => "thirty_one",
+        ScoreKind::Go => "go",
+        ScoreKind::LastCard => "last_card",
+    }
+}
@@ -1,33 +1,47 @@
 use clap::Parser;
 use cribbage::frame::Frame;
-use cribbage::game::{Deck, Hand};
+use cribbage::game::{cut_range, score_cut, Card, Deck, Hand, Skunk};
 use cribbage::handle::Handle;
+use cribbage::pegging::PeggingState;
+use cribbage::rules::{Deal, RuleSet};
+use cribbage::shuffle::{commitment, is_valid_commitment, is_valid_secret, verify_reveals};
+use itertools::Itertools;
 use std::io;
 use std::net::TcpListener;
+use std::process;
 
 #[derive(Parser)]
 struct ServerArgs {
     num_players: usize,
     #[arg(default_value_t = 31892)]
     port: u16,
+    /// Rules preset and any changes, e.g. standard,muggins or five-card
+    #[arg(long, default_value = "standard")]
+    rules: RuleSet,
+    /// Deal from this seed alone instead of everyone's secrets, so it always gives the same game
+    #[arg(long)]
+    reproducible: Option<String>,
 }
 
 struct Player {
     handle: Handle,
     name: String,
-    finished: bool,
+    score: u8,
+    hand: Hand, // Cards dealt this round, minus discards
 }
 
 struct Players {
     players: Vec<Player>,
+    rules: RuleSet,
     dealer_index: usize,
     player_index: usize,
 }
 
 impl Players {
-    pub fn from(players: Vec<Player>) -> Players {
+    pub fn from(players: Vec<Player>, rules: RuleSet) -> Players {
         Players {
             players,
+            rules,
             dealer_index: 0,
             player_index: 0,
         }
@@ -43,11 +57,24 @@ impl Players {
         dealer
     }
 
+    pub fn current_dealer(&self) -> usize {
+        let len = self.players.len();
+        (self.dealer_index + len - 1) % len
+    }
+
+    // The player to the dealer's right cuts the deck
+    pub fn cutter(&self) -> usize {
+        let len = self.players.len();
+        (self.current_dealer() + len - 1) % len
+    }
+
     pub fn start_play(&mut self) {
         self.player_index = self.dealer_index;
-        for player in self.players.iter_mut() {
-            player.finished = false;
-        }
+    }
+
+    // The player to the dealer's left goes first, which is also the next dealer
+    pub fn first_player(&self) -> usize {
+        self.dealer_index
     }
 
     pub fn next_player(&mut self) -> &mut Player {
@@ -60,18 +87,112 @@ impl Players {
         player
     }
 
-    pub fn decrement_player(&mut self) {
-        let len = self.players.len();
-        self.player_index = (self.player_index + len - 1) % len;
+    pub fn team(&self, index: usize) -> usize {
+        self.rules.team(index, self.players.len())
+    }
+
+    // Partners' names together, or just the player's when playing alone
+    pub fn team_name(&self, index: usize) -> String {
+        self.team_seats(index)
+            .into_iter()
+            .map(|other| self.players[other].name.as_str())
+            .join(" & ")
     }
 
-    pub fn players_finished(&self) -> bool {
-        self.players.iter().all(|player| player.finished)
+    pub fn team_seats(&self, index: usize) -> Vec<usize> {
+        self.rules.team_seats(index, self.players.len())
+    }
+
+    // Partners share a score, so points go to everyone on the scorer's team
+    pub fn add_score(&mut self, index: usize, points: u8) {
+        let target = self.rules.target;
+
+        for other in self.team_seats(index) {
+            let player = &mut self.players[other];
+            player.score = player.score.saturating_add(points).min(target);
+        }
+
+        if points > 0 {
+            let player = &self.players[index];
+            println!("{} scored {} ({})", player.name, points, player.score);
+        }
+    }
+
+    pub fn winner(&self) -> Option<usize> {
+        let target = self.rules.target;
+        self.players
+            .iter()
+            .position(|player| player.score >= target)
+    }
+
+    // Checked against the number of players before the server starts
+    pub fn deal(&self) -> Deal {
+        *self
+            .rules
+            .deal(self.players.len())
+            .expect("Rules don't support this many players")
+    }
+
+    pub fn broadcast(&mut self, frame: &Frame) -> Result<(), io::Error> {
+        for player in &mut self.players {
+            player.handle.send_frame(frame)?;
+        }
+
+        Ok(())
+    }
+
+    // Tells every client the current scores, or who won if the game is over
+    pub fn send_scores(&mut self) -> Result<bool, io::Error> {
+        let scores: Vec<u8> = self.players.iter().map(|p| p.score).collect();
+
+        let frame = if let Some(winner) = self.winner() {
+            let skunks = scores
+                .iter()
+                .enumerate()
+                .map(|(index, score)| {
+                    if self.team(index) == self.team(winner) {
+                        Skunk::None
+                    } else {
+                        self.rules.skunk(*score)
+                    }
+                })
+                .collect();
+
+            println!("{} wins!", self.team_name(winner));
+            Frame::GameOver(winner, scores, skunks)
+        } else {
+            Frame::Scores(scores)
+        };
+
+        for player in &mut self.players {
+            player.handle.send_frame(&frame)?;
+        }
+
+        Ok(self.winner().is_some())
     }
 }
 
 fn main() {
     let args = ServerArgs::parse();
+    let mut rules = args.rules.clone();
+
+    if rules.deal(args.num_players).is_none() {
+        eprintln!(
+            "Error: {} rules can't be played with {} players",
+            rules.name, args.num_players
+        );
+        process::exit(1);
+    }
+
+    if rules.reproducible && args.reproducible.is_none() {
+        eprintln!("Error: reproducible rules need a seed from --reproducible");
+        process::exit(1);
+    }
+
+    // Clients are told so they expect the seed's secret in place of their own
+    rules.reproducible = args.reproducible.is_some();
+
+    println!("Playing by {}", rules);
 
     let addr = format!("0.0.0.0:{}", args.port);
 
@@ -80,16 +201,20 @@ fn main() {
     let listener = TcpListener::bind(addr).expect("Failed to bind to address.");
 
     loop {
-        let mut players = collect_players(&listener, args.num_players);
+        let mut players = collect_players(&listener, args.num_players, &rules);
 
-        if let Err(e) = game_loop(&mut players, args.num_players) {
-            eprintln!("Error: {}", e);
+        match game_loop(&mut players, args.reproducible.as_deref()) {
+            Ok(()) => println!("Game over, back to the lobby"),
+            Err(e) => eprintln!("Error: {}", e),
         }
     }
 }
 
-fn collect_players(listener: &TcpListener, num_players: usize) -> Players {
+fn collect_players(listener: &TcpListener, num_players: usize, rules: &RuleSet) -> Players {
     let mut players: Vec<Player> = Vec::new();
+    let mut teams: Vec<Option<usize>> = Vec::new();
+    let num_teams = rules.num_teams(num_players);
+    let partners = num_teams < num_players;
 
     println!("Waiting for {} players...", num_players);
 
@@ -105,17 +230,25 @@ fn collect_players(listener: &TcpListener, num_players: usize) -> Players {
         let mut handle = Handle::new(stream);
 
         match handle.read_frame() {
-            Ok(Some(Frame::Name(name))) => {
+            Ok(Some(Frame::Name(name, team))) => {
                 println!("Player {} connected from {}", name, addr);
 
+                // Teams only mean something in a partnership game
+                let team = team.filter(|_| partners);
+
                 if players.iter().any(|player| player.name == name) {
                     println!("Duplicate name, disconnecting {}", addr);
+                } else if let Err(e) = check_team(&teams, team, num_teams, num_players) {
+                    println!("Rejected {}: {}", name, e);
+                    let _ = handle.send_frame(&Frame::Error(e));
                 } else {
                     players.push(Player {
                         handle,
                         name,
-                        finished: false,
+                        score: 0,
+                        hand: Hand::new(),
                     });
+                    teams.push(team);
                 }
             }
             Ok(None) => println!("{} disconnected", addr),
@@ -124,7 +257,70 @@ fn collect_players(listener: &TcpListener, num_players: usize) -> Players {
         }
     }
 
-    Players::from(players)
+    let players = Players::from(seat_players(players, teams, rules), rules.clone());
+
+    if partners {
+        for index in 0..num_teams {
+            println!("Team {}: {}", index + 1, players.team_name(index));
+        }
+    }
+
+    players
+}
+
+// A team can only be picked while it has a seat left
+fn check_team(
+    teams: &[Option<usize>],
+    team: Option<usize>,
+    num_teams: usize,
+    num_players: usize,
+) -> Result<(), String> {
+    let team = match team {
+        Some(team) => team,
+        None => return Ok(()),
+    };
+
+    if team >= num_teams {
+        return Err(format!("There are only {} teams", num_teams));
+    }
+
+    let taken = teams.iter().filter(|other| **other == Some(team)).count();
+
+    if taken >= num_players / num_teams {
+        return Err(format!("Team {} is full", team + 1));
+    }
+
+    Ok(())
+}
+
+// Players who picked a team take its seats in the order they joined, everyone else fills the
+// seats left over, so partners end up opposite each other
+fn seat_players(players: Vec<Player>, teams: Vec<Option<usize>>, rules: &RuleSet) -> Vec<Player> {
+    let num_players = players.len();
+    let mut seats: Vec<Option<Player>> = (0..num_players).map(|_| None).collect();
+    let mut unseated = Vec::new();
+
+    for (player, team) in players.into_iter().zip(teams) {
+        let seat = team.and_then(|team| {
+            (0..num_players)
+                .find(|seat| seats[*seat].is_none() && rules.team(*seat, num_players) == team)
+        });
+
+        match seat {
+            Some(seat) => seats[seat] = Some(player),
+            None => unseated.push(player),
+        }
+    }
+
+    let mut unseated = unseated.into_iter();
+
+    seats
+        .into_iter()
+        .map(|seat| {
+            seat.or_else(|| unseated.next())
+                .expect("More seats than players")
+        })
+        .collect()
 }
 
 fn send_start(players: &mut Players) -> Result<(), io::Error> {
@@ -134,7 +330,7 @@ fn send_start(players: &mut Players) -> Result<(), io::Error> {
         .map(|player| player.name.clone())
         .collect();
 
-    let start_frame = Frame::Start(names);
+    let start_frame = Frame::Start(names, players.rules.clone());
 
     for player in &mut players.players {
         player.handle.send_frame(&start_frame)?;
@@ -146,87 +342,193 @@ fn send_start(players: &mut Players) -> Result<(), io::Error> {
 fn deal(
     deck: &mut Deck,
     players: &mut Players,
-    num_players: usize,
-    seed: String,
-) -> Result<Hand, io::Error> {
-    let num_deal = 8 - num_players; // 2 players get 6, 3 players get 5
-    let mut crib = Hand::new();
+    round: usize,
+    secrets: &[String],
+) -> Result<Option<Hand>, io::Error> {
+    let rules_deal = players.deal();
+    let mut crib = Hand::new_crib();
+
+    // Logged so a disputed deal can be replayed with Deck::shuffle_seeded
+    let effective = deck.shuffle_secrets(secrets);
+    println!("Effective seed for round {}: {}", round, effective);
 
-    deck.shuffle(seed);
+    let (hands, mut stock) = rules_deal.deal_hands(deck, players.first_player());
 
     // Send each hand
-    for player in players.players.iter_mut() {
-        let hand = deck.deal(num_deal);
+    for (player, hand) in players.players.iter_mut().zip(hands) {
         println!("Dealing hand to {} ({})", player.name, hand);
-        player.handle.send_frame(&Frame::Hand(hand))?;
+        player.handle.send_frame(&Frame::Hand(hand.clone()))?;
+        player.hand = hand;
+    }
+
+    // Top up the crib from the stock when the discards leave it short
+    if rules_deal.from_stock > 0 {
+        println!("Dealing to the crib from the stock ({})", stock);
+        crib.combine(&mut stock);
     }
 
     // Get each discard
     for player in players.players.iter_mut() {
-        let mut discard_hand = match player.handle.read_frame()? {
-            Some(Frame::Hand(discard_hand)) => discard_hand,
-            _ => panic!("Bad discard hand from {}", player.name),
-        };
+        let mut discard_hand = get_discard(player, rules_deal.discard)?;
 
         println!("Received discard from {} ({})", player.name, discard_hand);
 
+        for card in discard_hand.cards() {
+            player.hand.remove_card(card);
+        }
+
         crib.combine(&mut discard_hand);
     }
 
     println!("Built crib!");
 
-    // Draw magic card
-    let magic = deck.draw_magic();
-    println!("Drew magic card: {}", magic);
+    // Player to the dealer's right cuts for the magic card
+    let cutter = players.cutter();
+    let position = get_cut(&mut players.players[cutter], deck.len())?;
+    let magic = deck.cut(position);
+    println!("Cut at {}, magic card: {}", position, magic);
     crib.set_magic(magic.clone());
-    let magic_frame = Frame::Card(magic.to_owned());
+    let magic_frame = Frame::Card(magic.clone());
 
     // Send magic card to clients
     for player in players.players.iter_mut() {
         player.handle.send_frame(&magic_frame)?;
     }
 
-    Ok(crib)
+    let dealer = players.current_dealer();
+    players.add_score(dealer, score_cut(&magic).total());
+
+    if players.send_scores()? {
+        return Ok(None);
+    }
+
+    Ok(Some(crib))
+}
+
+fn get_cut(cutter: &mut Player, deck_size: usize) -> Result<usize, io::Error> {
+    println!("Getting cut from {}...", cutter.name);
+    cutter.handle.send_frame(&Frame::Cut(deck_size))?;
+
+    loop {
+        let position = match cutter.handle.read_frame()? {
+            Some(Frame::Cut(position)) => position,
+            Some(_) => return Err(io::ErrorKind::InvalidData.into()),
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        };
+
+        let range = cut_range(deck_size);
+
+        if range.contains(&position) {
+            return Ok(position);
+        }
+
+        println!("Rejected cut from {}: {}", cutter.name, position);
+        let message = format!("Cut must be between {} and {}", range.start(), range.end());
+        cutter.handle.send_frame(&Frame::Error(message))?;
+    }
 }
 
-fn get_play(player: &mut Player) -> Result<Frame, io::Error> {
-    println!("Waiting for play from {}", player.name);
+fn get_discard(player: &mut Player, num_discard: usize) -> Result<Hand, io::Error> {
+    loop {
+        let discard_hand = match player.handle.read_frame()? {
+            Some(Frame::Hand(discard_hand)) => discard_hand,
+            Some(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Expected a discard from {}", player.name),
+                ))
+            }
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        };
 
-    let frame = match player.handle.read_frame()? {
-        Some(Frame::Play(card, out)) => {
-            if out {
-                player.finished = true;
+        match check_discard(&player.hand, &discard_hand, num_discard) {
+            Ok(()) => return Ok(discard_hand),
+            Err(e) => {
+                println!("Rejected discard from {}: {}", player.name, e);
+                player.handle.send_frame(&Frame::Error(e))?;
             }
-            Frame::Play(card, out)
         }
-        Some(Frame::RoundDone) => Frame::RoundDone,
-        Some(_) => return Err(io::ErrorKind::InvalidData.into()),
-        None => return Err(io::ErrorKind::UnexpectedEof.into()),
-    };
-    Ok(frame)
+    }
 }
 
-fn play(players: &mut Players) -> Result<(), io::Error> {
-    players.start_play();
+// Discards must be the right number of distinct cards from the dealt hand
+fn check_discard(hand: &Hand, discard_hand: &Hand, num_discard: usize) -> Result<(), String> {
+    if discard_hand.len() != num_discard {
+        return Err(format!("Must discard {} cards", num_discard));
+    }
+
+    if !discard_hand.cards().iter().all_unique() {
+        return Err(String::from("Can't discard the same card twice"));
+    }
+
+    if let Some(card) = discard_hand
+        .cards()
+        .iter()
+        .find(|card| !hand.cards().contains(card))
+    {
+        return Err(format!("{} was not dealt to you", card));
+    }
+
+    Ok(())
+}
+
+fn get_play(
+    player: &mut Player,
+    state: &PeggingState,
+    hand: &Hand,
+) -> Result<Option<Card>, io::Error> {
+    loop {
+        println!("Waiting for play from {}", player.name);
+
+        let card = match player.handle.read_frame()? {
+            Some(Frame::Play(card)) => card,
+            Some(_) => return Err(io::ErrorKind::InvalidData.into()),
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        };
+
+        match state.validate(hand, card.as_ref()) {
+            Ok(()) => return Ok(card),
+            Err(e) => {
+                println!("Rejected play from {}: {}", player.name, e);
+                player.handle.send_frame(&Frame::Error(e.to_string()))?;
+            }
+        }
+    }
+}
+
+// Returns true if someone won during the play
+fn play(players: &mut Players) -> Result<bool, io::Error> {
+    let kept = players.deal().kept();
+    let mut state = PeggingState::new(players.players.len(), players.first_player(), kept);
+    let mut hands: Vec<Hand> = players.players.iter().map(|p| p.hand.clone()).collect();
     println!("Starting play");
 
-    while !players.players_finished() {
-        let player = players.next_player();
+    while !state.is_finished() {
+        let index = state.current_player();
+        let player = &mut players.players[index];
 
-        let frame = get_play(player)?;
+        let card = get_play(player, &state, &hands[index])?;
 
         let name = player.name.clone();
 
-        if let Frame::RoundDone = frame {
-            println!("Client notified server round is done.");
-            players.decrement_player();
-            players.decrement_player();
-        } else {
-            forward_frame(players, frame, &name)?;
+        if let Some(card) = &card {
+            hands[index].remove_card(card);
+        }
+
+        let result = state
+            .play(card.clone())
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        forward_frame(players, Frame::Play(card), &name)?;
+
+        players.add_score(result.player, result.breakdown.total());
+
+        if players.send_scores()? {
+            return Ok(true);
         }
     }
 
-    Ok(())
+    Ok(false)
 }
 
 fn forward_frame(players: &mut Players, frame: Frame, name: &String) -> Result<(), io::Error> {
@@ -241,85 +543,213 @@ fn forward_frame(players: &mut Players, frame: Frame, name: &String) -> Result<(
     Ok(())
 }
 
-fn show(players: &mut Players, crib: Hand) -> Result<Vec<Hand>, io::Error> {
-    let mut hands = Vec::new();
+// Returns true if someone won while counting
+fn show(players: &mut Players, crib: &Hand) -> Result<bool, io::Error> {
+    let muggins = players.rules.muggins;
+    let magic = crib.magic().expect("Crib has no magic card").clone();
     players.start_play();
     println!("Starting show");
 
     for _ in 0..players.players.len() {
+        let index = players.player_index;
         let player = players.next_player();
 
-        println!("Waiting for hand from {}...", player.name);
-        let hand = match player.handle.read_frame()? {
-            Some(Frame::Hand(hand)) => hand,
-            Some(Frame::Play(card, bool)) => panic!(
-                "Got play {:?} {:?} from {} instead of hand!",
-                card, bool, player.name
-            ),
-            Some(_) => return Err(io::ErrorKind::InvalidData.into()),
-            None => return Err(io::ErrorKind::UnexpectedEof.into()),
-        };
+        // Show the hand we dealt, not one reported by the client
+        let mut hand = player.hand.clone();
+        hand.set_magic(magic.clone());
 
-        let name = player.name.clone();
-        drop(player);
+        println!("Showing hand from {} ({})", player.name, hand);
+        let hand_frame = Frame::Hand(hand.clone());
 
-        // Send hand to other players
-        println!("Forwarding hand to other players...");
-        forward_frame(players, Frame::Hand(hand.clone()), &name)?;
+        for player in &mut players.players {
+            player.handle.send_frame(&hand_frame)?;
+        }
+
+        score_show(players, index, hand.score().total(), muggins)?;
 
-        // Add hand to list
-        hands.push(hand);
+        if players.send_scores()? {
+            return Ok(true);
+        }
     }
 
     println!("Broadcasting crib...");
-    let crib_frame = Frame::Hand(crib);
+    let crib_frame = Frame::Hand(crib.clone());
 
     for player in &mut players.players {
         player.handle.send_frame(&crib_frame)?;
     }
 
-    Ok(hands)
+    let dealer = players.current_dealer();
+    score_show(players, dealer, crib.score().total(), muggins)?;
+
+    players.send_scores()
 }
 
-fn get_seed(dealer: &mut Player) -> Result<String, io::Error> {
-    println!("Getting seed from {}...", dealer.name);
-    let seed = match dealer.handle.read_frame()? {
-        Some(Frame::Seed(seed)) => seed,
-        Some(_) => return Err(io::ErrorKind::InvalidData.into()),
-        None => return Err(io::ErrorKind::UnexpectedEof.into()),
+// Scores a shown hand, letting the owner count it first when playing muggins
+fn score_show(
+    players: &mut Players,
+    owner: usize,
+    points: u8,
+    muggins: bool,
+) -> Result<(), io::Error> {
+    if !muggins {
+        players.add_score(owner, points);
+        return Ok(());
+    }
+
+    // Owner only gets what they claim, up to what the hand is worth
+    let claim = get_claim(&mut players.players[owner])?;
+    println!(
+        "{} claimed {} of {}",
+        players.players[owner].name, claim, points
+    );
+    players.broadcast(&Frame::Claimed(owner, claim))?;
+    players.add_score(owner, claim.min(points));
+
+    let mut missed = points.saturating_sub(claim);
+    let len = players.players.len();
+
+    // Opponents get a turn to take missed points, in order from the owner's left
+    for index in (1..len).map(|offset| (owner + offset) % len) {
+        if players.team(index) == players.team(owner) {
+            continue;
+        }
+
+        let claim = get_claim(&mut players.players[index])?;
+
+        // Claiming more than was missed gets nothing, as does claiming after the game is won
+        let taken = if claim <= missed && players.winner().is_none() {
+            claim
+        } else {
+            0
+        };
+
+        if claim > 0 {
+            println!(
+                "{} called muggins for {}, took {}",
+                players.players[index].name, claim, taken
+            );
+        }
+
+        missed -= taken;
+        players.broadcast(&Frame::Claimed(index, taken))?;
+        players.add_score(index, taken);
+    }
+
+    Ok(())
+}
+
+fn get_claim(player: &mut Player) -> Result<u8, io::Error> {
+    match player.handle.read_frame()? {
+        Some(Frame::Claim(points)) => Ok(points),
+        Some(_) => Err(io::ErrorKind::InvalidData.into()),
+        None => Err(io::ErrorKind::UnexpectedEof.into()),
+    }
+}
+
+// Every player commits to a secret, sees everyone else's commitment, then reveals it. Given a
+// fixed secret, the players' secrets are still checked but only the fixed one is published
+fn get_secrets(players: &mut Players, fixed: Option<String>) -> Result<Vec<String>, io::Error> {
+    println!("Getting commitments...");
+    let mut commits = Vec::new();
+
+    for player in &mut players.players {
+        match player.handle.read_frame()? {
+            Some(Frame::Commit(commit)) if is_valid_commitment(&commit) => commits.push(commit),
+            Some(_) => return Err(io::ErrorKind::InvalidData.into()),
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+
+    let published = match &fixed {
+        Some(secret) => vec![commitment(secret)],
+        None => commits.clone(),
     };
 
-    Ok(seed)
+    players.broadcast(&Frame::Commits(published))?;
+
+    println!("Getting secrets...");
+    let mut secrets = Vec::new();
+
+    for player in &mut players.players {
+        let secret = match player.handle.read_frame()? {
+            Some(Frame::Reveal(secret)) if is_valid_secret(&secret) => secret,
+            Some(_) => return Err(io::ErrorKind::InvalidData.into()),
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        };
+
+        println!("Secret from {}: {}", player.name, secret);
+        secrets.push(secret);
+    }
+
+    if !verify_reveals(&commits, &secrets) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Secret doesn't match its commitment",
+        ));
+    }
+
+    Ok(fixed.map_or(secrets, |secret| vec![secret]))
 }
 
-fn game_loop(players: &mut Players, num_players: usize) -> Result<(), io::Error> {
+fn game_loop(players: &mut Players, seed: Option<&str>) -> Result<(), io::Error> {
     send_start(players)?;
 
     let mut deck = Deck::new();
 
+    let mut round = 0;
+
     loop {
+        round += 1;
+
         let dealer = players.next_dealer();
         println!("Dealer = {}", dealer.name);
 
-        // Get seed from dealer
-        let seed = get_seed(dealer)?;
+        // The pone gets points for going second, 3 for last in five card
+        let bonus = players.rules.pone_bonus;
 
-        // Deal
-        let crib = deal(&mut deck, players, num_players, seed)?;
+        if round == 1 && bonus > 0 {
+            let pone = players.first_player();
+            players.add_score(pone, bonus);
+            players.send_scores()?;
+        }
+
+        // Shuffle from everyone's secrets, or a secret made from the seed and round when
+        // reproducible, which is still a valid secret clients can check the deal against
+        let fixed = seed.map(|seed| commitment(&format!("{}/{}", seed, round)));
+        let secrets = get_secrets(players, fixed)?;
 
-        // Play
-        play(players)?;
+        // Deal, play and show, stopping as soon as someone wins
+        let crib = deal(&mut deck, players, round, &secrets)?;
 
-        // Show
-        let hands = show(players, crib.clone())?;
+        let won = match &crib {
+            Some(crib) => play(players)? || show(players, crib)?,
+            None => true,
+        };
 
-        // Recover deck
-        for hand in hands {
+        // Publish the secrets so everyone can check the deal, even the one that ended the game
+        players.broadcast(&Frame::Reveals(secrets))?;
+
+        let crib = match crib {
+            Some(crib) if !won => crib,
+            _ => return Ok(()),
+        };
+
+        // Recover deck from the cards we dealt
+        for player in &mut players.players {
+            let hand = std::mem::take(&mut player.hand);
             println!("Recovered hand: {}", hand);
             deck.rejoin(hand);
         }
 
         println!("Recovered crib: {}", crib);
         deck.rejoin(crib);
+
+        if !deck.is_complete() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Recovered deck is missing cards",
+            ));
+        }
     }
 }
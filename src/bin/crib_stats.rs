@@ -0,0 +1,76 @@
+use clap::Parser;
+use cribbage::stats::{self, MAX_SCORE};
+
+#[derive(Parser)]
+struct StatsArgs {
+    /// Look up where a score falls instead of printing the tables
+    #[arg(long)]
+    percentile: Option<u8>,
+    /// Look up the score as a crib
+    #[arg(long)]
+    crib: bool,
+    /// Print the tables as the constants embedded in the library
+    #[arg(long)]
+    rust: bool,
+}
+
+fn main() {
+    let args = StatsArgs::parse();
+
+    if let Some(score) = args.percentile {
+        println!(
+            "{} {}: beats or ties {:.4}% of {}, top {:.4}%",
+            score,
+            if args.crib {
+                "point crib"
+            } else {
+                "point hand"
+            },
+            stats::percentile(score, args.crib),
+            if args.crib { "cribs" } else { "hands" },
+            stats::top_percent(score, args.crib)
+        );
+        return;
+    }
+
+    // Count everything from scratch rather than trusting the embedded tables
+    let hands = stats::frequencies(false);
+    let cribs = stats::frequencies(true);
+
+    if args.rust {
+        print_constant("HAND_FREQUENCIES", &hands);
+        print_constant("CRIB_FREQUENCIES", &cribs);
+        return;
+    }
+
+    println!("Score       Hands        %       Cribs        %");
+
+    for score in 0..=MAX_SCORE {
+        println!(
+            "{:>5} {:>11} {:>8.4} {:>11} {:>8.4}",
+            score,
+            hands[score],
+            stats::percent(hands[score]),
+            cribs[score],
+            stats::percent(cribs[score])
+        );
+    }
+
+    println!(
+        "Total {:>11} {:>8} {:>11}",
+        hands.iter().sum::<u64>(),
+        "",
+        cribs.iter().sum::<u64>()
+    );
+    println!("Average hand {:.4}", stats::average_of(&hands));
+    println!("Average crib {:.4}", stats::average_of(&cribs));
+}
+
+fn print_constant(name: &str, counts: &[u64]) {
+    let values: Vec<String> = counts.iter().map(|count| count.to_string()).collect();
+    println!(
+        "pub const {}: [u64; MAX_SCORE + 1] = [{}];",
+        name,
+        values.join(", ")
+    );
+}
@@ -0,0 +1,116 @@
+use clap::Parser;
+use cribbage::analysis::{crib_expectation, starter_stats, CribTable};
+use cribbage::game::Card;
+use cribbage::score::score_hand;
+use cribbage::CardSet;
+use itertools::Itertools;
+use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Parser)]
+struct TableArgs {
+    /// File to write the table to
+    output: PathBuf,
+    /// Deals simulated for each discard pair
+    #[arg(long, default_value_t = 2000)]
+    samples: usize,
+    /// Seed for the simulation, random if not given
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+fn main() {
+    let args = TableArgs::parse();
+
+    if let Err(e) = crib_tables(args) {
+        eprintln!("Error: {}", e);
+    }
+}
+
+fn crib_tables(args: TableArgs) -> Result<(), io::Error> {
+    let mut rng = match args.seed {
+        Some(seed) => SmallRng::seed_from_u64(seed),
+        None => SmallRng::from_entropy(),
+    };
+
+    // Start from every pair of cards being equally likely to join ours
+    println!("Computing exact crib values against random discards...");
+    let mut baseline = CribTable::new();
+
+    for (a, b) in CribTable::keys() {
+        let value = crib_expectation([&a, &b].into_iter().collect(), CardSet::new(), 2);
+        baseline.set(&a, &b, true, value);
+        baseline.set(&a, &b, false, value);
+    }
+
+    // Then have the other player throw the way the baseline tells them to
+    let mut table = CribTable::new();
+
+    for own_crib in [true, false] {
+        println!(
+            "Simulating {} deals per pair for the {}...",
+            args.samples,
+            if own_crib { "dealer" } else { "pone" }
+        );
+
+        for (a, b) in CribTable::keys() {
+            let value = simulate(&a, &b, own_crib, &baseline, args.samples, &mut rng);
+            table.set(&a, &b, own_crib, value);
+        }
+    }
+
+    table.save(&args.output)?;
+    println!("Wrote crib table to {}", args.output.display());
+
+    Ok(())
+}
+
+// Average crib when we throw a and b, the other player throws their best pair and a starter is cut
+fn simulate(
+    a: &Card,
+    b: &Card,
+    own_crib: bool,
+    baseline: &CribTable,
+    samples: usize,
+    rng: &mut SmallRng,
+) -> f64 {
+    let discard: CardSet = [a, b].into_iter().collect();
+    let mut deck: Vec<Card> = discard.complement().iter().collect();
+    let mut total = 0;
+
+    for _ in 0..samples {
+        deck.shuffle(rng);
+
+        // Our other four cards, the other player's six and the starter
+        let their_hand: CardSet = deck[4..10].iter().collect();
+        let starter = &deck[10];
+
+        // Value every discard once, then keep the best
+        let (their_discard, _) = their_hand
+            .iter()
+            .tuple_combinations()
+            .map(|(c, d): (Card, Card)| {
+                let thrown: CardSet = [&c, &d].into_iter().collect();
+                let hand = starter_stats(their_hand - thrown, thrown, false).expected;
+
+                // Their crib when it isn't ours
+                let value = if own_crib {
+                    hand - baseline.get(&c, &d, false)
+                } else {
+                    hand + baseline.get(&c, &d, true)
+                };
+
+                ((c, d), value)
+            })
+            .max_by(|(_, x), (_, y)| x.total_cmp(y))
+            .expect("Other player has no cards");
+
+        let crib = discard | [&their_discard.0, &their_discard.1].into_iter().collect();
+        total += score_hand(crib, starter, true) as usize;
+    }
+
+    total as f64 / samples.max(1) as f64
+}
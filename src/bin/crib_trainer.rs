@@ -0,0 +1,203 @@
+use clap::Parser;
+use cribbage::game::{Deck, Hand, ScoreBreakdown, ScoreKind};
+use itertools::Itertools;
+use std::collections::HashMap;
+use std::io;
+
+#[derive(Parser)]
+struct TrainerArgs {
+    /// Count cribs, which only score a flush when the magic card matches
+    #[arg(long)]
+    crib: bool,
+    /// Deal more hands with the kind of scoring you get wrong the most
+    #[arg(long)]
+    focus: bool,
+}
+
+// Kinds of scoring that can turn up in a shown hand
+const HAND_KINDS: [ScoreKind; 5] = [
+    ScoreKind::Fifteen,
+    ScoreKind::Pair,
+    ScoreKind::Run,
+    ScoreKind::Flush,
+    ScoreKind::Nob,
+];
+
+// How often each kind of scoring came up and how often the count was wrong when it did
+#[derive(Default)]
+struct Session {
+    hands: usize,
+    correct: usize,
+    seen: HashMap<ScoreKind, usize>,
+    missed: HashMap<ScoreKind, usize>,
+}
+
+impl Session {
+    pub fn record(&mut self, breakdown: &ScoreBreakdown, correct: bool) {
+        self.hands += 1;
+
+        if correct {
+            self.correct += 1;
+        }
+
+        for kind in breakdown.events().iter().map(|event| event.kind).unique() {
+            *self.seen.entry(kind).or_default() += 1;
+
+            if !correct {
+                *self.missed.entry(kind).or_default() += 1;
+            }
+        }
+    }
+
+    // Kind of scoring with the worst accuracy, if any have been got wrong yet
+    pub fn weakest(&self) -> Option<ScoreKind> {
+        HAND_KINDS
+            .into_iter()
+            .filter(|kind| self.missed.get(kind).is_some_and(|missed| *missed > 0))
+            .max_by(|a, b| self.miss_rate(*a).total_cmp(&self.miss_rate(*b)))
+    }
+
+    fn miss_rate(&self, kind: ScoreKind) -> f64 {
+        let seen = self.seen.get(&kind).copied().unwrap_or(0);
+        let missed = self.missed.get(&kind).copied().unwrap_or(0);
+        missed as f64 / seen.max(1) as f64
+    }
+
+    pub fn print_summary(&self) {
+        println!(
+            "\n{} of {} counted correctly ({:.0}%)",
+            self.correct,
+            self.hands,
+            percent(self.correct, self.hands)
+        );
+
+        for kind in HAND_KINDS {
+            let seen = self.seen.get(&kind).copied().unwrap_or(0);
+
+            if seen == 0 {
+                continue;
+            }
+
+            let right = seen - self.missed.get(&kind).copied().unwrap_or(0);
+
+            println!(
+                "  {:<8} {} of {} ({:.0}%)",
+                kind_name(kind),
+                right,
+                seen,
+                percent(right, seen)
+            );
+        }
+    }
+}
+
+fn main() {
+    let args = TrainerArgs::parse();
+
+    if let Err(e) = trainer(args) {
+        eprintln!("Error: {}", e);
+    }
+}
+
+fn trainer(args: TrainerArgs) -> Result<(), io::Error> {
+    let mut session = Session::default();
+    let mut rng = rand::thread_rng();
+
+    println!("Count each hand, or enter q to quit");
+
+    loop {
+        let focus = if args.focus { session.weakest() } else { None };
+        let hand = deal_hand(&mut rng, args.crib, focus);
+        let breakdown = hand.score();
+
+        println!("\n{} + Magic Card", if args.crib { "Crib" } else { "Hand" });
+        hand.pretty_print(false, true);
+
+        let guess = match prompt_user_count()? {
+            Some(guess) => guess,
+            None => break,
+        };
+
+        let correct = guess == breakdown.total();
+
+        if correct {
+            println!("Correct!");
+        } else {
+            println!("Not quite, you said {}", guess);
+        }
+
+        println!("{}", breakdown);
+        session.record(&breakdown, correct);
+    }
+
+    session.print_summary();
+
+    Ok(())
+}
+
+// Deals four cards and a magic card, looking for one with the focus kind of scoring for a while
+fn deal_hand(rng: &mut impl rand::Rng, crib: bool, focus: Option<ScoreKind>) -> Hand {
+    let mut tries = 0;
+
+    loop {
+        let mut deck = Deck::new();
+        deck.shuffle_with(rng);
+
+        let mut hand = deck.deal(5);
+        let magic = hand.remove(4);
+        hand.set_magic(magic);
+        hand.set_crib(crib);
+
+        tries += 1;
+
+        let matches = match focus {
+            Some(kind) => hand.score().events().iter().any(|event| event.kind == kind),
+            None => true,
+        };
+
+        if matches || tries >= 1000 {
+            return hand;
+        }
+    }
+}
+
+// Returns None when the user quits
+fn prompt_user_count() -> Result<Option<u8>, io::Error> {
+    let mut buf = String::new();
+
+    loop {
+        println!("Count: (points)");
+
+        if io::stdin().read_line(&mut buf)? == 0 {
+            return Ok(None);
+        }
+
+        let input = buf.trim();
+
+        if input.eq_ignore_ascii_case("q") {
+            return Ok(None);
+        }
+
+        if let Ok(points) = input.parse() {
+            return Ok(Some(points));
+        }
+
+        println!("Invalid input. Try again.");
+        buf.clear();
+    }
+}
+
+fn kind_name(kind: ScoreKind) -> &'static str {
+    match kind {
+        ScoreKind::Fifteen => "Fifteens",
+        ScoreKind::Pair => "Pairs",
+        ScoreKind::Run => "Runs",
+        ScoreKind::Flush => "Flushes",
+        ScoreKind::Nob => "Nob",
+        _ => "Other",
+    }
+}
+
+fn percent(part: usize, whole: usize) -> f64 {
+    100.0 * part as f64 / whole.max(1) as f64
+}
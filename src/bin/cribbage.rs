@@ -1,17 +1,37 @@
 use clap::Parser;
+use cribbage::analysis::{rank_discards, rank_discards_with_table, CribTable};
 use cribbage::frame::Frame;
-use cribbage::game::{is_run, Card, Hand};
+use cribbage::game::{cut_range, score_cut, Card, Deck, Hand, Skunk};
 use cribbage::handle::Handle;
+use cribbage::pegging::PeggingState;
+use cribbage::rules::{Deal, RuleSet};
+use cribbage::shuffle::{commitment, new_secret, verify_reveals};
+use cribbage::CardSet;
 use itertools::Itertools;
+use rand::Rng;
 use std::cmp::{max, min};
-use std::collections::HashMap;
 use std::io;
 use std::net::TcpStream;
+use std::path::PathBuf;
 
 #[derive(Parser)]
 struct ClientArgs {
     name: String,
     addr: String,
+    /// Show the expected value of every discard before choosing
+    #[arg(long)]
+    advise: bool,
+    /// Crib table written by crib_tables, makes two card discard advice instant
+    #[arg(long, requires = "advise")]
+    crib_table: Option<PathBuf>,
+    /// Team to play for in a four player partnership game, 1 or 2, partners pick the same one
+    #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+    team: Option<u8>,
+}
+
+// Shows the expected value of every discard, using a crib table if one was loaded
+struct Advisor {
+    table: Option<CribTable>,
 }
 
 struct Player {
@@ -33,14 +53,15 @@ impl Player {
         }
     }
 
-    pub fn add_play_score(&mut self, score: u8) {
-        self.play_score += score;
-        self.score = min(self.score + score, 121);
+    // Scores come from the server, the difference is what was scored this time
+    pub fn set_play_score(&mut self, score: u8) {
+        self.play_score += score.saturating_sub(self.score);
+        self.score = score;
     }
 
-    pub fn add_show_score(&mut self, score: u8) {
-        self.show_score += score;
-        self.score = min(self.score + score, 121);
+    pub fn set_show_score(&mut self, score: u8) {
+        self.show_score += score.saturating_sub(self.score);
+        self.score = score;
     }
 
     pub fn hand(&self) -> &Hand {
@@ -48,18 +69,36 @@ impl Player {
     }
 }
 
+// Sent by the server whenever anything is scored
+enum ScoreUpdate {
+    Scores(Vec<u8>),
+    GameOver(usize, Vec<u8>, Vec<Skunk>),
+}
+
+impl ScoreUpdate {
+    pub fn is_game_over(&self) -> bool {
+        matches!(self, ScoreUpdate::GameOver(..))
+    }
+}
+
 struct Players {
     pub players: Vec<Player>,
+    rules: RuleSet,
     dealer_index: usize,
     player_index: usize,
+    winner: Option<usize>,
+    skunks: Vec<Skunk>,
 }
 
 impl Players {
-    pub fn from(names: Vec<String>) -> Players {
+    pub fn from(names: Vec<String>, rules: RuleSet) -> Players {
         Players {
             players: names.into_iter().map(Player::from_name).collect_vec(),
+            rules,
             dealer_index: 0,
             player_index: 0,
+            winner: None,
+            skunks: Vec::new(),
         }
     }
 
@@ -75,13 +114,22 @@ impl Players {
         let min_score = self.players.iter().map(|p| p.score).min().unwrap() as isize;
         let max_score = self.max_score() as isize;
 
-        let min_print = max(min_score - 5, 0);
-        let max_print = min(max_score + 5, 121);
+        let target = self.rules.target as isize;
+
+        // Short games fit the whole board on a line, long ones show the holes around the pegs
+        let (min_print, max_print) = if target <= 61 {
+            (0, target)
+        } else {
+            (max(min_score - 5, 0), min(max_score + 5, target))
+        };
 
         //| ----- ---o- -----
         //| --o-- ----- -----
 
-        for player in &self.players {
+        // Partners share a peg, so there's a row for each team
+        for index in self.team_leaders() {
+            let player = &self.players[index];
+
             for i in min_print..=max_print {
                 if i == 0 {
                     if player.score == 0 {
@@ -104,20 +152,48 @@ impl Players {
 
             println!(
                 " ({}) {} (+{}p +{}s)",
-                player.score, player.name, player.play_score, player.show_score
+                player.score,
+                self.team_name(index),
+                player.play_score,
+                player.show_score
             );
         }
     }
 
-    pub fn max_score(&self) -> u8 {
-        self.players.iter().map(|p| p.score).max().unwrap()
+    pub fn update_play_scores(&mut self, update: ScoreUpdate) {
+        let scores = self.final_scores(update);
+
+        for (player, score) in self.players.iter_mut().zip(scores) {
+            player.set_play_score(score);
+        }
     }
 
-    pub fn player_with_max_score(&self) -> &Player {
-        self.players
-            .iter()
-            .max_by_key(|p| p.score)
-            .expect("No players found")
+    pub fn update_show_scores(&mut self, update: ScoreUpdate) {
+        let scores = self.final_scores(update);
+
+        for (player, score) in self.players.iter_mut().zip(scores) {
+            player.set_show_score(score);
+        }
+    }
+
+    // Records the result if the game is over
+    fn final_scores(&mut self, update: ScoreUpdate) -> Vec<u8> {
+        match update {
+            ScoreUpdate::Scores(scores) => scores,
+            ScoreUpdate::GameOver(winner, scores, skunks) => {
+                self.winner = Some(winner);
+                self.skunks = skunks;
+                scores
+            }
+        }
+    }
+
+    pub fn is_game_over(&self) -> bool {
+        self.winner.is_some()
+    }
+
+    pub fn max_score(&self) -> u8 {
+        self.players.iter().map(|p| p.score).max().unwrap()
     }
 
     pub fn next_dealer(&mut self) -> String {
@@ -141,6 +217,11 @@ impl Players {
         self.player_index = self.dealer_index;
     }
 
+    // The player to the dealer's left goes first, which is also the next dealer
+    pub fn first_player(&self) -> usize {
+        self.dealer_index
+    }
+
     pub fn next_player(&mut self) -> &mut Player {
         let len = self.players.len();
         let player = self
@@ -151,19 +232,44 @@ impl Players {
         player
     }
 
-    pub fn peek_player(&self) -> &Player {
+    pub fn index_of(&self, name: &str) -> usize {
         self.players
-            .get(self.player_index)
-            .expect("No player found")
-    }
-
-    pub fn decrement_player(&mut self) {
-        self.player_index = (self.player_index + self.players.len() - 1) % self.players.len();
+            .iter()
+            .position(|player| player.name == name)
+            .expect("Player not found")
     }
 
     pub fn len(&self) -> usize {
         self.players.len()
     }
+
+    pub fn team(&self, index: usize) -> usize {
+        self.rules.team(index, self.len())
+    }
+
+    // Partners' names together, or just the player's when playing alone
+    pub fn team_name(&self, index: usize) -> String {
+        self.rules
+            .team_seats(index, self.len())
+            .into_iter()
+            .map(|other| self.players[other].name.as_str())
+            .join(" & ")
+    }
+
+    // First player on each team, whose score stands for the whole team
+    pub fn team_leaders(&self) -> Vec<usize> {
+        (0..self.len())
+            .unique_by(|index| self.team(*index))
+            .collect()
+    }
+
+    // The server won't start a game its rules can't deal for
+    pub fn deal(&self) -> Deal {
+        *self
+            .rules
+            .deal(self.len())
+            .expect("Rules don't support this many players")
+    }
 }
 
 fn main() {
@@ -182,65 +288,219 @@ fn cribbage(args: ClientArgs) -> Result<(), io::Error> {
     println!("Connected to server!");
 
     // Send name packet to server
-    handle.send_frame(&Frame::Name(args.name.clone()))?;
+    let team = args.team.map(|team| team as usize - 1);
+    handle.send_frame(&Frame::Name(args.name.clone(), team))?;
 
     // Wait for start packet
     println!("Waiting for players...");
 
-    let names = match handle.read_frame()? {
-        Some(Frame::Start(names)) => names,
+    let (names, rules) = match handle.read_frame()? {
+        Some(Frame::Start(names, rules)) => (names, rules),
+        Some(Frame::Error(message)) => return Err(io::Error::other(message)),
         Some(_) => return Err(io::ErrorKind::InvalidData.into()),
         None => return Err(io::ErrorKind::UnexpectedEof.into()),
     };
 
     println!("Game starting with players: {:?}", names);
+    println!("Playing {} rules to {}", rules.name, rules.target);
+
+    if rules.muggins {
+        println!("Playing muggins, count your own hands!");
+    }
 
-    let players = Players::from(names);
+    if rules.reproducible {
+        println!("Deals come from the server's seed, your secret won't change them");
+    }
 
-    game_loop(&mut handle, players, args.name)?;
+    if rules.deal(names.len()).is_none() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Rules don't support this many players",
+        ));
+    }
+
+    let players = Players::from(names, rules);
+
+    if players.team_leaders().len() < players.len() {
+        for (team, index) in players.team_leaders().into_iter().enumerate() {
+            println!("Team {}: {}", team + 1, players.team_name(index));
+        }
+    }
+
+    let advisor = if args.advise {
+        let table = match &args.crib_table {
+            Some(path) => Some(CribTable::load(path)?),
+            None => None,
+        };
+
+        Some(Advisor { table })
+    } else {
+        None
+    };
+
+    game_loop(&mut handle, players, args.name, advisor.as_ref())?;
 
     Ok(())
 }
 
-fn send_seed(handle: &mut Handle) -> Result<(), io::Error> {
-    let mut seed = String::new();
+// Commits to a random secret for the shuffle and reveals it once everyone has committed
+// Returns the secret and the commitments the server published
+fn send_secret(
+    handle: &mut Handle,
+    players: &Players,
+    me: usize,
+) -> Result<(String, Vec<String>), io::Error> {
+    let secret = new_secret();
+    handle.send_frame(&Frame::Commit(commitment(&secret)))?;
+
+    let commits = match handle.read_frame()? {
+        Some(Frame::Commits(commits)) => commits,
+        Some(_) => return Err(io::ErrorKind::InvalidData.into()),
+        None => return Err(io::ErrorKind::UnexpectedEof.into()),
+    };
+
+    // Revealing a secret the deal won't use would let the server pick the cards
+    let ours = if players.rules.reproducible {
+        commits.len() == 1
+    } else {
+        commits.len() == players.len() && commits[me] == commitment(&secret)
+    };
+
+    if !ours {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "Server didn't publish our commitment, refusing to reveal our secret",
+        ));
+    }
 
-    println!("Provide seed for random shuffle:");
-    io::stdin().read_line(&mut seed)?;
-    let seed = String::from(seed.trim());
+    handle.send_frame(&Frame::Reveal(secret.clone()))?;
 
-    handle.send_frame(&Frame::Seed(seed))?;
+    Ok((secret, commits))
+}
+
+// Rebuilds the deck from everyone's secrets and checks it gives the cards we were dealt
+fn verify_deal(
+    handle: &mut Handle,
+    players: &Players,
+    me: usize,
+    secret: &str,
+    commits: &[String],
+    dealt: &Hand,
+) -> Result<(), io::Error> {
+    let secrets = match handle.read_frame()? {
+        Some(Frame::Reveals(secrets)) => secrets,
+        Some(_) => return Err(io::ErrorKind::InvalidData.into()),
+        None => return Err(io::ErrorKind::UnexpectedEof.into()),
+    };
+
+    if !verify_reveals(commits, &secrets) {
+        println!("Warning: a secret doesn't match its commitment, the deal can't be trusted!");
+        return Ok(());
+    }
+
+    // Only a server that announced its seed may leave our secret out of the shuffle
+    let ours = if players.rules.reproducible {
+        secrets.len() == 1
+    } else {
+        secrets.len() == players.len() && secrets[me] == secret
+    };
+
+    if !ours {
+        println!("Warning: the deal didn't use our secret, it can't be trusted!");
+        return Ok(());
+    }
+
+    let mut deck = Deck::new();
+    deck.shuffle_secrets(&secrets);
+
+    let (hands, stock) = players.deal().deal_hands(&mut deck, players.first_player());
+
+    // The magic card has to come from what was left after the deal
+    let magic_dealt = dealt.magic().is_some_and(|magic| {
+        hands.iter().any(|hand| hand.cards().contains(magic)) || stock.cards().contains(magic)
+    });
+
+    let source = if players.rules.reproducible {
+        "the server's seed"
+    } else {
+        "everyone's secrets"
+    };
+
+    if CardSet::from(&hands[me]) == CardSet::from(dealt) && !magic_dealt {
+        println!("Deal verified against {}", source);
+    } else {
+        println!("Warning: the deal doesn't match everyone's secrets!");
+    }
 
     Ok(())
 }
 
-fn game_loop(handle: &mut Handle, mut players: Players, name: String) -> Result<(), io::Error> {
-    while players.max_score() < 121 {
+fn game_loop(
+    handle: &mut Handle,
+    mut players: Players,
+    name: String,
+    advisor: Option<&Advisor>,
+) -> Result<(), io::Error> {
+    let mut first_deal = true;
+    let me = players.index_of(&name);
+
+    while !players.is_game_over() {
+        players.reset_round();
+
         let dealer = players.next_dealer();
         println!("Dealer: {}", dealer);
 
-        if dealer == name {
-            send_seed(handle)?;
-        } else {
-            println!("Waiting for shuffle...");
+        // Server scores the pone's bonus before anyone commits to the shuffle
+        if first_deal && players.rules.pone_bonus > 0 {
+            let pone = &players.players[players.first_player()].name;
+            println!(
+                "{} gets {} for going second",
+                pone, players.rules.pone_bonus
+            );
+            players.update_play_scores(read_scores(handle)?);
         }
 
-        let hand = get_hand(handle, &mut players)?;
+        first_deal = false;
 
-        play(handle, &hand, &mut players, &name)?;
+        println!("Waiting for shuffle...");
+        let (secret, commits) = send_secret(handle, &players, me)?;
 
-        show(handle, hand, &mut players, &name)?;
+        // A partner's crib counts for us too
+        let own_crib = players.team(players.index_of(&dealer)) == players.team(me);
+        let (dealt, hand) = get_hand(handle, &mut players, advisor, own_crib)?;
 
-        players.reset_round();
+        if !players.is_game_over() {
+            play(handle, &hand, &mut players, &name)?;
+        }
+
+        if !players.is_game_over() {
+            if players.rules.muggins {
+                show_muggins(handle, &mut players, &name)?;
+            } else {
+                show(handle, &mut players)?;
+            }
+        }
+
+        // Secrets are published after every hand, including the one that ends the game
+        verify_deal(handle, &players, me, &secret, &commits, &dealt)?;
     }
 
-    let winner = players.player_with_max_score();
+    println!("\nFinal scores:");
+    players.print_scores();
 
-    println!("{} wins!", winner.name);
+    let winner = players.winner.expect("Game ended without a winner");
 
-    for player in players.players {
-        if player.score <= 90 {
-            println!("{} got skunked!!! 🦨🤢🦨🤮", player.name);
+    let partners = players.team_leaders().len() < players.len();
+    let verb = if partners { "win" } else { "wins" };
+    println!("{} {}!", players.team_name(winner), verb);
+
+    for index in players.team_leaders() {
+        let name = players.team_name(index);
+
+        match players.skunks.get(index) {
+            Some(Skunk::Single) => println!("{} got skunked!!! 🦨🤢🦨🤮", name),
+            Some(Skunk::Double) => println!("{} got double skunked!!! 🦨🦨🤮🤮", name),
+            _ => (),
         }
     }
 
@@ -253,64 +513,77 @@ fn wait_enter() {
     io::stdin().read_line(&mut input).unwrap();
 }
 
-fn show(
-    handle: &mut Handle,
-    hand: Hand,
-    players: &mut Players,
-    name: &String,
-) -> Result<(), io::Error> {
+fn show(handle: &mut Handle, players: &mut Players) -> Result<(), io::Error> {
     players.start_play();
     println!("\nShow!");
 
-    // Setup my hand
-    let hand_frame = Frame::Hand(hand.clone());
-    players
-        .players
-        .iter_mut()
-        .find(|p| p.name == *name)
-        .unwrap()
-        .hand = Some(hand);
+    // Receive every hand from the server, which scores each one as it goes
+    let mut hand_scores = Vec::new();
 
-    // Receive player hands & send mine
     for _ in 0..players.len() {
         let player = players.next_player();
 
-        if &player.name == name {
-            handle.send_frame(&hand_frame)?;
-        } else {
-            let recv_hand = match handle.read_frame()? {
-                Some(Frame::Hand(hand)) => hand,
-                Some(_) => return Err(io::ErrorKind::InvalidData.into()),
-                None => return Err(io::ErrorKind::UnexpectedEof.into()),
-            };
+        let hand = match handle.read_frame()? {
+            Some(Frame::Hand(hand)) => hand,
+            Some(_) => return Err(io::ErrorKind::InvalidData.into()),
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        };
+
+        player.hand = Some(hand);
+
+        let update = read_scores(handle)?;
+        let game_over = update.is_game_over();
+        hand_scores.push(update);
 
-            player.hand = Some(recv_hand);
+        // Nothing more is counted once somebody wins
+        if game_over {
+            break;
         }
     }
 
     // Receive crib
-    let crib = match handle.read_frame()? {
-        Some(Frame::Hand(crib)) => crib,
-        Some(_) => return Err(io::ErrorKind::InvalidData.into()),
-        None => return Err(io::ErrorKind::UnexpectedEof.into()),
+    let crib = if hand_scores
+        .last()
+        .is_some_and(|update| update.is_game_over())
+    {
+        None
+    } else {
+        let crib = match handle.read_frame()? {
+            Some(Frame::Hand(crib)) => crib,
+            Some(_) => return Err(io::ErrorKind::InvalidData.into()),
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        };
+
+        Some((crib, read_scores(handle)?))
     };
 
     // Display hands
-    for _ in 0..players.len() {
+    players.start_play();
+
+    for update in hand_scores {
         let player = players.next_player();
 
         println!("{}'s Hand + Magic Card", player.name);
         player.hand().pretty_print(false, true);
-        player.add_show_score(player.hand().score());
+        println!("{}", player.hand().score());
+        print_rarity(player.hand());
+        players.update_show_scores(update);
         wait_enter();
     }
 
     // Display crib
-    let dealer = players.current_dealer();
-    println!("{}'s Crib + Magic Card", dealer.name);
-    crib.pretty_print(false, true);
-    dealer.add_show_score(crib.score());
-    wait_enter();
+    if let Some((crib, update)) = crib {
+        println!("{}'s Crib + Magic Card", players.current_dealer().name);
+        crib.pretty_print(false, true);
+        println!("{}", crib.score());
+        print_rarity(&crib);
+        players.update_show_scores(update);
+        wait_enter();
+    }
+
+    if players.is_game_over() {
+        return Ok(());
+    }
 
     // Display scores
     println!("Scores:");
@@ -320,133 +593,177 @@ fn show(
     Ok(())
 }
 
-fn get_play(
-    handle: &mut Handle,
-    round_count: u8,
-    name: &String,
-    player: &String,
-    playing_hand: &mut Hand,
-) -> Result<(Option<Card>, bool), io::Error> {
-    if player == name {
-        if playing_hand.len() == 0 {
-            println!("No cards left. Go!");
-            handle.send_frame(&Frame::Play(None, true))?;
-            return Ok((None, true));
-        }
-
-        let playable_hand = Hand::from(
-            playing_hand
-                .cards()
-                .to_vec()
-                .into_iter()
-                .filter(|card| card.score_value() <= 31 - round_count)
-                .collect_vec(),
-            None,
-        );
-
-        if playable_hand.len() == 0 {
-            println!("No playable cards. Go!");
-            handle.send_frame(&Frame::Play(None, false))?;
-            return Ok((None, false));
-        }
+// Each hand is counted by its owner as it's shown, then everyone else can claim what was missed
+fn show_muggins(handle: &mut Handle, players: &mut Players, name: &str) -> Result<(), io::Error> {
+    println!("\nShow!");
 
-        let card = prompt_user_play(playable_hand)?;
-        println!("Playing: {}", card);
-        playing_hand.remove_card(&card);
-        handle.send_frame(&Frame::Play(Some(card.clone()), playing_hand.len() == 0))?;
-        return Ok((Some(card), playing_hand.len() == 0));
-    } else {
-        // Wait for player
-        println!("Waiting for {}...", player);
+    let len = players.len();
+    let first = players.first_player();
+    let dealer = (first + len - 1) % len;
 
-        match handle.read_frame()? {
-            Some(Frame::Play(card, out)) => {
-                if let Some(card) = &card {
-                    println!("{} played {}", player, card);
-                } else {
-                    println!("{} couldn't play. Go!", player);
-                }
+    // Hands from the dealer's left, then the crib
+    let owners = (0..len)
+        .map(|offset| (first + offset) % len)
+        .chain([dealer]);
 
-                return Ok((card, out));
-            }
+    for (i, owner) in owners.enumerate() {
+        let hand = match handle.read_frame()? {
+            Some(Frame::Hand(hand)) => hand,
             Some(_) => return Err(io::ErrorKind::InvalidData.into()),
             None => return Err(io::ErrorKind::UnexpectedEof.into()),
         };
-    }
-}
 
-fn score_play(play_history: &Vec<Card>) -> u8 {
-    let mut score: u8 = 0;
+        let owner_name = players.players[owner].name.clone();
+        let kind = if i == len { "Crib" } else { "Hand" };
 
-    // Check if play is a run
-    let history_len = play_history.len();
-    if history_len > 2 {
-        let longest_run: Option<Vec<&Card>> = (0..history_len - 2)
-            .collect::<Vec<usize>>()
-            .into_iter()
-            .map(|num_drop| {
-                play_history
-                    .iter()
-                    .dropping(num_drop)
-                    .sorted_by(|a, b| a.order().cmp(&b.order()))
-                    .collect::<Vec<&Card>>()
-            })
-            .filter(|sorted_subset| is_run(&sorted_subset))
-            .max_by(|a, b| a.len().cmp(&b.len()));
-
-        if let Some(run) = longest_run {
-            let run_length = run.len();
-            score += run_length as u8;
-            println!(
-                "Run of {} for {}! ({})",
-                run_length,
-                score,
-                run.iter().join(", ")
-            );
+        println!("{}'s {} + Magic Card", owner_name, kind);
+        hand.pretty_print(false, true);
+
+        if owner_name == name {
+            let points = prompt_user_count("Count your hand:")?;
+            handle.send_frame(&Frame::Claim(points))?;
         }
-    }
 
-    // Check if play is a pair, triplet...
-    let num_matching = play_history
-        .iter()
-        .rev()
-        .skip(1)
-        .take_while(|card| card.value == play_history.last().unwrap().value)
-        .count()
-        + 1;
+        let claim = read_claimed(handle, owner)?;
+        println!("{} claimed {}", owner_name, claim);
+
+        // Only opponents can call muggins
+        for index in (1..len).map(|offset| (owner + offset) % len) {
+            if players.team(index) == players.team(owner) {
+                continue;
+            }
+
+            if players.players[index].name == name {
+                let prompt = format!("Points {} missed: (0 to pass)", owner_name);
+                let points = prompt_user_count(&prompt)?;
+                handle.send_frame(&Frame::Claim(points))?;
+            }
 
-    if num_matching >= 2 {
-        score += (num_matching * (num_matching - 1)) as u8;
+            let taken = read_claimed(handle, index)?;
 
-        match num_matching {
-            2 => println!("Pair for {}!", score),
-            3 => println!("Triplet for {}!!", score),
-            4 => println!("Quadruplet for {}!!!", score),
-            _ => panic!("More than four cards of the same rank in a play!"),
+            if taken > 0 {
+                println!(
+                    "Muggins! {} took {} missed points",
+                    players.players[index].name, taken
+                );
+            }
+        }
+
+        println!("{}", hand.score());
+        print_rarity(&hand);
+
+        let update = read_scores(handle)?;
+        let game_over = update.is_game_over();
+        players.update_show_scores(update);
+        wait_enter();
+
+        // Nothing more is counted once somebody wins
+        if game_over {
+            return Ok(());
         }
     }
 
-    // Check if play adds up to 15 (2 points)
-    let sum = play_history
-        .iter()
-        .fold(0, |acc, card| acc + card.score_value());
+    // Display scores
+    println!("Scores:");
+    players.print_scores();
+    wait_enter();
+
+    Ok(())
+}
 
-    if sum == 15 {
-        score += 2;
-        println!("15 for {}!", score);
+// Reads what the server says a player claimed, which has to be the player we expect
+fn read_claimed(handle: &mut Handle, player: usize) -> Result<u8, io::Error> {
+    match handle.read_frame()? {
+        Some(Frame::Claimed(index, points)) if index == player => Ok(points),
+        Some(_) => Err(io::ErrorKind::InvalidData.into()),
+        None => Err(io::ErrorKind::UnexpectedEof.into()),
     }
+}
 
-    // Check if play adds up to 31 (2 points)
-    if sum == 31 {
-        score += 2;
-        println!("31 for {}!", score);
+fn read_scores(handle: &mut Handle) -> Result<ScoreUpdate, io::Error> {
+    match handle.read_frame()? {
+        Some(frame) => score_update(frame),
+        None => Err(io::ErrorKind::UnexpectedEof.into()),
     }
+}
 
-    score
+fn score_update(frame: Frame) -> Result<ScoreUpdate, io::Error> {
+    match frame {
+        Frame::Scores(scores) => Ok(ScoreUpdate::Scores(scores)),
+        Frame::GameOver(winner, scores, skunks) => {
+            Ok(ScoreUpdate::GameOver(winner, scores, skunks))
+        }
+        _ => Err(io::ErrorKind::InvalidData.into()),
+    }
 }
 
-fn players_finished(player_status: &HashMap<String, bool>) -> bool {
-    player_status.values().all(|finished| *finished)
+// Points out hands that don't come along often
+fn print_rarity(hand: &Hand) {
+    let top = match hand.top_percent() {
+        Some(top) => top,
+        None => return,
+    };
+
+    if top < 0.01 {
+        println!("That's in the top 0.01% of hands!");
+    } else if top <= 10.0 {
+        println!("That's in the top {:.2}% of hands!", top);
+    }
+}
+
+fn get_play(
+    handle: &mut Handle,
+    state: &PeggingState,
+    name: &String,
+    player: &String,
+    playing_hand: &mut Hand,
+) -> Result<(Option<Card>, ScoreUpdate), io::Error> {
+    if player == name {
+        loop {
+            let playable_hand = Hand::from(state.legal_moves(playing_hand), None);
+
+            let card = if playable_hand.is_empty() {
+                println!("No playable cards. Go!");
+                None
+            } else {
+                let card = prompt_user_play(playable_hand)?;
+                println!("Playing: {}", card);
+                Some(card)
+            };
+
+            handle.send_frame(&Frame::Play(card.clone()))?;
+
+            // Server either accepts the play and sends scores, or rejects it
+            match handle.read_frame()? {
+                Some(Frame::Error(message)) => println!("Play rejected: {}", message),
+                Some(frame) => {
+                    if let Some(card) = &card {
+                        playing_hand.remove_card(card);
+                    }
+
+                    return Ok((card, score_update(frame)?));
+                }
+                None => return Err(io::ErrorKind::UnexpectedEof.into()),
+            }
+        }
+    } else {
+        // Wait for player
+        println!("Waiting for {}...", player);
+
+        let card = match handle.read_frame()? {
+            Some(Frame::Play(card)) => card,
+            Some(_) => return Err(io::ErrorKind::InvalidData.into()),
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        };
+
+        if let Some(card) = &card {
+            println!("{} played {}", player, card);
+        } else {
+            println!("{} couldn't play. Go!", player);
+        }
+
+        Ok((card, read_scores(handle)?))
+    }
 }
 
 fn play(
@@ -456,77 +773,37 @@ fn play(
     name: &String,
 ) -> Result<(), io::Error> {
     let mut playing_hand = Hand::from(hand.cards().to_vec(), None);
-    let mut play_history: Vec<Card> = Vec::new();
-    let mut round_count: u8 = 0;
-    let mut go_count: usize = 0;
-    let num_players = players.len();
+    let mut state = PeggingState::new(players.len(), players.first_player(), hand.len());
 
     println!("\nPlay!");
 
-    let mut player_status: HashMap<String, bool> = players
-        .players
-        .iter()
-        .map(|player| (player.name.clone(), false))
-        .collect();
+    while !state.is_finished() {
+        println!("\nCount: {}", state.count());
 
-    players.start_play();
+        let player_name = players.players[state.current_player()].name.clone();
+        let (card, update) = get_play(handle, &state, name, &player_name, &mut playing_hand)?;
 
-    loop {
-        while round_count < 31 {
-            let player = players.next_player();
-            println!("\nCount: {}", round_count);
-
-            let (played_card, player_out) =
-                get_play(handle, round_count, name, &player.name, &mut playing_hand)?;
-
-            if let Some(card) = played_card {
-                round_count += card.score_value();
-                play_history.push(card);
-                let score = score_play(&play_history);
-                player.add_play_score(score);
-                go_count = 0;
-            } else {
-                go_count += 1;
-            }
+        let result = state
+            .play(card)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
-            if go_count == num_players {
-                println!("{} scored 1 for go!", player.name);
-                player.add_play_score(1);
-                break;
-            }
+        for event in result.breakdown.events() {
+            println!("{}: {}", players.players[result.player].name, event);
+        }
 
-            if player_out {
-                let player_entry = player_status
-                    .get_mut(&player.name)
-                    .expect("Player not found in player status map");
-                if player_entry == &false {
-                    *player_entry = true;
-                }
+        players.update_play_scores(update);
 
-                if players_finished(&player_status) {
-                    if round_count < 31 {
-                        println!("{} is last with cards! Go for 1!\n", player.name);
-                        player.add_play_score(1);
-                    }
-                    
-                    return Ok(());
-                }
-            }
+        if players.is_game_over() {
+            return Ok(());
         }
 
-        println!("End of round!");
-        if &players.peek_player().name == name {
-            // Server expects a frame from me next
-            handle.send_frame(&Frame::RoundDone)?;
+        if result.reset && !state.is_finished() {
+            println!("End of round!");
+            std::thread::sleep(std::time::Duration::from_secs(2));
         }
-
-        play_history.clear();
-        round_count = 0;
-        go_count = 0;
-        players.decrement_player();
-
-        std::thread::sleep(std::time::Duration::from_secs(2));
     }
+
+    Ok(())
 }
 
 fn prompt_user_play(mut playable_hand: Hand) -> Result<Card, io::Error> {
@@ -535,15 +812,13 @@ fn prompt_user_play(mut playable_hand: Hand) -> Result<Card, io::Error> {
     loop {
         println!("Playable cards:");
         playable_hand.pretty_print(true, false);
-        println!("Select a card to play: (i)");
+        println!("Select a card to play: (i or card, e.g. 10h)");
 
         io::stdin().read_line(&mut buf)?;
 
-        if let Ok(index) = buf.trim().parse() {
-            if index < playable_hand.len() {
-                let card = playable_hand.remove(index);
-                return Ok(card);
-            }
+        if let Some(index) = card_index(&playable_hand, buf.trim()) {
+            let card = playable_hand.remove(index);
+            return Ok(card);
         }
 
         println!("Invalid input. Try again.");
@@ -551,24 +826,64 @@ fn prompt_user_play(mut playable_hand: Hand) -> Result<Card, io::Error> {
     }
 }
 
-fn prompt_user_discard(num: usize, max_index: u8) -> Result<Vec<u8>, io::Error> {
+fn print_advice(
+    advisor: &Advisor,
+    hand: &Hand,
+    num_discard: usize,
+    crib_size: usize,
+    own_crib: bool,
+) {
+    let options = match &advisor.table {
+        // Tables are built from six card deals
+        Some(table) if num_discard == 2 && hand.len() == 6 => {
+            rank_discards_with_table(hand, table, own_crib)
+        }
+        _ => {
+            // Everyone else's discards and any cards from the stock are unknown
+            let unknown = crib_size - num_discard;
+            rank_discards(hand, num_discard, unknown, own_crib)
+        }
+    };
+
+    println!(
+        "Discard advice ({} crib):",
+        if own_crib { "your" } else { "their" }
+    );
+
+    for option in options {
+        println!(
+            "  {:<10} hand {:>5.2}  crib {:>5.2}  net {:>6.2}",
+            option.discard.iter().join(" "),
+            option.hand,
+            option.crib,
+            option.net
+        );
+    }
+}
+
+fn prompt_user_discard(num: usize, hand: &Hand) -> Result<Vec<usize>, io::Error> {
     let mut buf = String::new();
-    let mut indices: Vec<u8>;
+    let mut indices: Vec<usize>;
 
     let prompt = if num == 2 {
-        "Select two cards to discard: (i,j)"
+        "Select two cards to discard: (i,j or cards, e.g. 5h,JS)"
     } else {
-        "Select a card to discard: (i)"
+        "Select a card to discard: (i or card, e.g. 5h)"
     };
 
     loop {
         println!("{}", prompt);
         io::stdin().read_line(&mut buf)?;
 
-        indices = buf.trim().split(',').flat_map(|s| s.parse().ok()).collect();
+        indices = buf
+            .trim()
+            .split(',')
+            .flat_map(|s| card_index(hand, s.trim()))
+            .unique()
+            .collect();
 
-        if indices.len() == num && indices.iter().max().unwrap() < &max_index {
-            indices.sort_by(|a, b| b.partial_cmp(a).unwrap());
+        if indices.len() == num {
+            indices.sort_by(|a, b| b.cmp(a));
             return Ok(indices);
         }
 
@@ -577,7 +892,90 @@ fn prompt_user_discard(num: usize, max_index: u8) -> Result<Vec<u8>, io::Error>
     }
 }
 
-fn get_hand(handle: &mut Handle, players: &mut Players) -> Result<Hand, io::Error> {
+// Finds a card in the hand by its index or by its name, like 10h, TH or A♠
+fn card_index(hand: &Hand, input: &str) -> Option<usize> {
+    if let Ok(index) = input.parse() {
+        return if index < hand.len() {
+            Some(index)
+        } else {
+            None
+        };
+    }
+
+    let card: Card = input.parse().ok()?;
+    hand.cards().iter().position(|c| c == &card)
+}
+
+fn prompt_user_count(prompt: &str) -> Result<u8, io::Error> {
+    let mut buf = String::new();
+
+    loop {
+        println!("{}", prompt);
+        io::stdin().read_line(&mut buf)?;
+
+        if let Ok(points) = buf.trim().parse() {
+            return Ok(points);
+        }
+
+        println!("Invalid input. Try again.");
+        buf.clear();
+    }
+}
+
+fn cut_deck(handle: &mut Handle, deck_size: usize) -> Result<Frame, io::Error> {
+    loop {
+        let position = prompt_user_cut(deck_size)?;
+        handle.send_frame(&Frame::Cut(position))?;
+
+        match handle.read_frame()? {
+            Some(Frame::Error(message)) => println!("Cut rejected: {}", message),
+            Some(frame) => return Ok(frame),
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        }
+    }
+}
+
+fn prompt_user_cut(deck_size: usize) -> Result<usize, io::Error> {
+    let mut buf = String::new();
+    let range = cut_range(deck_size);
+
+    // The server sends the size of the stock, so it can't be trusted to leave a cut
+    if range.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("Can't cut a stock of {} cards", deck_size),
+        ));
+    }
+
+    loop {
+        println!(
+            "Cut the deck: ({}-{}, or enter for a random cut)",
+            range.start(),
+            range.end()
+        );
+        io::stdin().read_line(&mut buf)?;
+
+        if buf.trim().is_empty() {
+            return Ok(rand::thread_rng().gen_range(range));
+        }
+
+        if let Ok(position) = buf.trim().parse() {
+            if range.contains(&position) {
+                return Ok(position);
+            }
+        }
+
+        println!("Invalid input. Try again.");
+        buf.clear();
+    }
+}
+
+fn get_hand(
+    handle: &mut Handle,
+    players: &mut Players,
+    advisor: Option<&Advisor>,
+    own_crib: bool,
+) -> Result<(Hand, Hand), io::Error> {
     // Wait for hand
     let mut hand = match handle.read_frame()? {
         Some(Frame::Hand(hand)) => hand,
@@ -588,41 +986,63 @@ fn get_hand(handle: &mut Handle, players: &mut Players) -> Result<Hand, io::Erro
     println!("\nHand:");
     hand.pretty_print(true, false);
 
-    let num_discard = 4 - players.len();
+    let deal = players.deal();
+    let num_discard = deal.discard;
 
-    let discard = prompt_user_discard(num_discard, hand.len().try_into().unwrap())?;
+    if let Some(advisor) = advisor {
+        print_advice(advisor, &hand, num_discard, deal.crib_size(), own_crib);
+    }
 
-    let mut discard_hand = Hand::new();
+    let mut dealt = hand.clone();
 
-    // Remove discarded cards from hand
-    for i in discard {
-        discard_hand.push(hand.remove(i.into()));
-    }
+    let mut frame = loop {
+        let discard = prompt_user_discard(num_discard, &hand)?;
 
-    println!("Discarding... ({})", discard_hand.cards().iter().join(", "));
+        let mut kept_hand = hand.clone();
+        let mut discard_hand = Hand::new();
 
-    // Send discard to server
-    handle.send_frame(&Frame::Hand(discard_hand))?;
+        // Remove discarded cards from hand
+        for i in discard {
+            discard_hand.push(kept_hand.remove(i));
+        }
 
-    // Wait for magic card
-    let magic = match handle.read_frame()? {
-        Some(Frame::Card(magic)) => magic,
-        Some(_) => return Err(io::ErrorKind::InvalidData.into()),
-        None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        println!("Discarding... ({})", discard_hand.cards().iter().join(", "));
+
+        // Send discard to server
+        handle.send_frame(&Frame::Hand(discard_hand))?;
+
+        // Wait for the server to move on, or try again if it rejected the discard
+        match handle.read_frame()? {
+            Some(Frame::Error(message)) => println!("Discard rejected: {}", message),
+            Some(frame) => {
+                hand = kept_hand;
+                break frame;
+            }
+            None => return Err(io::ErrorKind::UnexpectedEof.into()),
+        }
     };
 
-    println!("Magic card dealt! ({})", magic);
+    // Server asks us to cut if we're to the dealer's right
+    if let Frame::Cut(deck_size) = frame {
+        frame = cut_deck(handle, deck_size)?;
+    }
 
-    // Score flipping a jack
-    if magic.value == 'J' {
-        println!(
-            "{} scored 2 for flipping a jack!",
-            players.current_dealer().name
-        );
-        players.current_dealer().add_play_score(2);
+    let magic = match frame {
+        Frame::Card(magic) => magic,
+        _ => return Err(io::ErrorKind::InvalidData.into()),
+    };
+
+    println!("Magic card cut! ({})", magic);
+
+    for event in score_cut(&magic).events() {
+        println!("{}: {}", players.current_dealer().name, event);
     }
 
+    players.update_play_scores(read_scores(handle)?);
+
+    dealt.set_magic(magic.clone());
     hand.set_magic(magic);
 
-    Ok(hand)
+    // Everything we were dealt, and what we kept
+    Ok((dealt, hand))
 }
@@ -0,0 +1,269 @@
+use crate::game::{Card, Hand, Rank, Suit};
+use itertools::Itertools;
+use std::fmt;
+use std::ops::{BitAnd, BitAndAssign, BitOr, BitOrAssign, Not, Sub, SubAssign};
+
+// A set of cards stored as one bit per card, bit n is the card with index n
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Default, Debug)]
+pub struct CardSet(u64);
+
+impl CardSet {
+    pub const EMPTY: CardSet = CardSet(0);
+    pub const FULL: CardSet = CardSet((1 << 52) - 1);
+
+    // One bit for the ace of each suit, shifting by a rank gives every card of that rank
+    const ACES: u64 = 1 | 1 << 13 | 1 << 26 | 1 << 39;
+
+    pub fn new() -> CardSet {
+        CardSet::EMPTY
+    }
+
+    // Bits above the 52nd are dropped
+    pub fn from_bits(bits: u64) -> CardSet {
+        CardSet(bits & CardSet::FULL.0)
+    }
+
+    pub fn bits(&self) -> u64 {
+        self.0
+    }
+
+    pub fn contains(&self, card: &Card) -> bool {
+        self.contains_index(card.index())
+    }
+
+    pub fn contains_index(&self, index: u8) -> bool {
+        index < 52 && self.0 & (1 << index) != 0
+    }
+
+    // Returns false if the card was already in the set
+    pub fn insert(&mut self, card: &Card) -> bool {
+        let had = self.contains(card);
+        self.0 |= 1 << card.index();
+        !had
+    }
+
+    // Returns false if the card wasn't in the set
+    pub fn remove(&mut self, card: &Card) -> bool {
+        let had = self.contains(card);
+        self.0 &= !(1 << card.index());
+        had
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    pub fn is_subset(&self, other: CardSet) -> bool {
+        self.0 & !other.0 == 0
+    }
+
+    pub fn union(self, other: CardSet) -> CardSet {
+        CardSet(self.0 | other.0)
+    }
+
+    pub fn intersection(self, other: CardSet) -> CardSet {
+        CardSet(self.0 & other.0)
+    }
+
+    pub fn difference(self, other: CardSet) -> CardSet {
+        CardSet(self.0 & !other.0)
+    }
+
+    // Every card in the deck that isn't in this set
+    pub fn complement(self) -> CardSet {
+        CardSet(!self.0 & CardSet::FULL.0)
+    }
+
+    // Cards of one suit, bit n is the rank with order n + 1
+    pub fn suit_mask(&self, suit: Suit) -> u16 {
+        ((self.0 >> (suit as u8 * 13)) & 0x1FFF) as u16
+    }
+
+    // How many cards of each rank the set holds, indexed from ace to king
+    pub fn rank_counts(&self) -> [u8; 13] {
+        let mut counts = [0; 13];
+
+        for (rank, count) in counts.iter_mut().enumerate() {
+            *count = ((self.0 >> rank) & CardSet::ACES).count_ones() as u8;
+        }
+
+        counts
+    }
+
+    pub fn iter(&self) -> Iter {
+        Iter(self.0)
+    }
+
+    pub fn indices(&self) -> impl Iterator<Item = u8> {
+        let bits = self.0;
+        (0..52).filter(move |index| bits & (1 << index) != 0)
+    }
+
+    // Cards in index order, without a magic card
+    pub fn to_hand(&self) -> Hand {
+        Hand::from(self.iter().collect(), None)
+    }
+
+    pub fn of_rank(rank: Rank) -> CardSet {
+        CardSet(CardSet::ACES << rank as u8)
+    }
+
+    pub fn of_suit(suit: Suit) -> CardSet {
+        CardSet(0x1FFF << (suit as u8 * 13))
+    }
+}
+
+// Yields the cards of a set from the lowest index up
+#[derive(Clone)]
+pub struct Iter(u64);
+
+impl Iterator for Iter {
+    type Item = Card;
+
+    fn next(&mut self) -> Option<Card> {
+        if self.0 == 0 {
+            return None;
+        }
+
+        let index = self.0.trailing_zeros() as u8;
+        self.0 &= self.0 - 1;
+
+        Card::from_index(index)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.count_ones() as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Iter {}
+
+impl IntoIterator for CardSet {
+    type Item = Card;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for &CardSet {
+    type Item = Card;
+    type IntoIter = Iter;
+
+    fn into_iter(self) -> Iter {
+        self.iter()
+    }
+}
+
+impl FromIterator<Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = Card>>(iter: I) -> CardSet {
+        let mut set = CardSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl<'a> FromIterator<&'a Card> for CardSet {
+    fn from_iter<I: IntoIterator<Item = &'a Card>>(iter: I) -> CardSet {
+        let mut set = CardSet::new();
+        set.extend(iter);
+        set
+    }
+}
+
+impl Extend<Card> for CardSet {
+    fn extend<I: IntoIterator<Item = Card>>(&mut self, iter: I) {
+        for card in iter {
+            self.insert(&card);
+        }
+    }
+}
+
+impl<'a> Extend<&'a Card> for CardSet {
+    fn extend<I: IntoIterator<Item = &'a Card>>(&mut self, iter: I) {
+        for card in iter {
+            self.insert(card);
+        }
+    }
+}
+
+impl From<&Card> for CardSet {
+    fn from(card: &Card) -> CardSet {
+        CardSet(1 << card.index())
+    }
+}
+
+impl From<&[Card]> for CardSet {
+    fn from(cards: &[Card]) -> CardSet {
+        cards.iter().collect()
+    }
+}
+
+// Only the cards in the hand, the magic card is left out
+impl From<&Hand> for CardSet {
+    fn from(hand: &Hand) -> CardSet {
+        hand.cards().iter().collect()
+    }
+}
+
+impl BitOr for CardSet {
+    type Output = CardSet;
+
+    fn bitor(self, other: CardSet) -> CardSet {
+        self.union(other)
+    }
+}
+
+impl BitOrAssign for CardSet {
+    fn bitor_assign(&mut self, other: CardSet) {
+        *self = self.union(other);
+    }
+}
+
+impl BitAnd for CardSet {
+    type Output = CardSet;
+
+    fn bitand(self, other: CardSet) -> CardSet {
+        self.intersection(other)
+    }
+}
+
+impl BitAndAssign for CardSet {
+    fn bitand_assign(&mut self, other: CardSet) {
+        *self = self.intersection(other);
+    }
+}
+
+impl Sub for CardSet {
+    type Output = CardSet;
+
+    fn sub(self, other: CardSet) -> CardSet {
+        self.difference(other)
+    }
+}
+
+impl SubAssign for CardSet {
+    fn sub_assign(&mut self, other: CardSet) {
+        *self = self.difference(other);
+    }
+}
+
+impl Not for CardSet {
+    type Output = CardSet;
+
+    fn not(self) -> CardSet {
+        self.complement()
+    }
+}
+
+impl fmt::Display for CardSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.iter().join(", "))
+    }
+}
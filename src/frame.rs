@@ -1,11 +1,20 @@
-use crate::game::{Card, Hand};
+use crate::game::{Card, Hand, Skunk};
+use crate::rules::RuleSet;
 
 pub enum Frame {
-    Name(String),             // Client sends name to server
-    Start(Vec<String>),       // Server tells client game starts, includes list of names
-    Hand(Hand),               // Cribbage hand (cards + optional magic)
-    Card(Card),               // Single card
-    Play(Option<Card>, bool), // A single move (card played, is player out of cards)
-    RoundDone,                // Client tells server a round is done
-    Seed(String),             // Random seed provided by dealer for shuffling
+    Name(String, Option<usize>), // Client sends name to server, and the team it wants
+    Start(Vec<String>, RuleSet), // Game starts, includes list of names and the rules
+    Hand(Hand),                  // Cribbage hand (cards + optional magic)
+    Card(Card),                  // Single card
+    Play(Option<Card>),          // A single move (card played or go)
+    Commit(String),              // Client sends the hash of its secret for the shuffle
+    Scores(Vec<u8>),             // Server tells clients everyone's score, in start order
+    GameOver(usize, Vec<u8>, Vec<Skunk>), // Winner's index, final scores and skunks
+    Error(String),               // Server rejected the last frame, client should retry
+    Cut(usize),                  // Deck size when asked to cut, position when cutting
+    Claim(u8),                   // Client counts their hand, or the points others missed
+    Claimed(usize, u8),          // Server tells clients what a player claimed or took
+    Commits(Vec<String>),        // Server sends every player's commitment, in start order
+    Reveal(String),              // Client reveals its secret once every commitment is in
+    Reveals(Vec<String>),        // Server publishes every secret after the hand
 }
@@ -1,10 +1,12 @@
+use crate::shuffle;
+use crate::stats;
 use itertools::Itertools;
-use rand::rngs::SmallRng;
 use rand::seq::SliceRandom;
-use rand::{Rng, SeedableRng};
-use std::collections::hash_map::DefaultHasher;
+use rand::Rng;
 use std::fmt;
-use std::hash::{Hash, Hasher};
+use std::io;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
 
 #[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
 pub enum Suit {
@@ -14,91 +16,259 @@ pub enum Suit {
     Clubs,
 }
 
+impl Suit {
+    pub const ALL: [Suit; 4] = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
+
+    pub fn to_net_name(&self) -> char {
+        match self {
+            Suit::Spades => 'S',
+            Suit::Hearts => 'H',
+            Suit::Diamonds => 'D',
+            Suit::Clubs => 'C',
+        }
+    }
+}
+
+impl TryFrom<char> for Suit {
+    type Error = ParseCardError;
+
+    // Accepts letters in either case, and both filled and outlined symbols
+    fn try_from(c: char) -> Result<Suit, ParseCardError> {
+        match c.to_ascii_uppercase() {
+            'S' | '♠' | '♤' => Ok(Suit::Spades),
+            'H' | '♥' | '♡' => Ok(Suit::Hearts),
+            'D' | '♦' | '♢' => Ok(Suit::Diamonds),
+            'C' | '♣' | '♧' => Ok(Suit::Clubs),
+            _ => Err(ParseCardError::Suit(c.to_string())),
+        }
+    }
+}
+
+impl FromStr for Suit {
+    type Err = ParseCardError;
+
+    fn from_str(s: &str) -> Result<Suit, ParseCardError> {
+        let mut chars = s.trim().chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Suit::try_from(c),
+            (None, _) => Err(ParseCardError::Empty),
+            _ => Err(ParseCardError::Suit(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Suit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Suit::Spades => "♤",
+                Suit::Hearts => "♥",
+                Suit::Diamonds => "♦",
+                Suit::Clubs => "♧",
+            }
+        )
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Rank {
+    Ace,
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    Jack,
+    Queen,
+    King,
+}
+
+impl Rank {
+    pub const ALL: [Rank; 13] = [
+        Rank::Ace,
+        Rank::Two,
+        Rank::Three,
+        Rank::Four,
+        Rank::Five,
+        Rank::Six,
+        Rank::Seven,
+        Rank::Eight,
+        Rank::Nine,
+        Rank::Ten,
+        Rank::Jack,
+        Rank::Queen,
+        Rank::King,
+    ];
+
+    pub fn score_value(&self) -> u8 {
+        self.order().min(10)
+    }
+
+    // Ace is low, king is 13
+    pub fn order(&self) -> u8 {
+        *self as u8 + 1
+    }
+
+    pub fn to_net_name(&self) -> char {
+        match self {
+            Rank::Ace => 'A',
+            Rank::Ten => 'T',
+            Rank::Jack => 'J',
+            Rank::Queen => 'Q',
+            Rank::King => 'K',
+            _ => char::from(b'0' + self.order()),
+        }
+    }
+}
+
+impl TryFrom<char> for Rank {
+    type Error = ParseCardError;
+
+    fn try_from(c: char) -> Result<Rank, ParseCardError> {
+        match c.to_ascii_uppercase() {
+            'A' | '1' => Ok(Rank::Ace),
+            '2'..='9' => Ok(Rank::ALL[c as usize - '1' as usize]),
+            'T' => Ok(Rank::Ten),
+            'J' => Ok(Rank::Jack),
+            'Q' => Ok(Rank::Queen),
+            'K' => Ok(Rank::King),
+            _ => Err(ParseCardError::Rank(c.to_string())),
+        }
+    }
+}
+
+impl FromStr for Rank {
+    type Err = ParseCardError;
+
+    // Accepts "10" as well as the single character ranks
+    fn from_str(s: &str) -> Result<Rank, ParseCardError> {
+        let s = s.trim();
+        let mut chars = s.chars();
+
+        match (chars.next(), chars.next()) {
+            (None, _) => Err(ParseCardError::Empty),
+            (Some(c), None) => Rank::try_from(c),
+            _ if s == "10" => Ok(Rank::Ten),
+            _ => Err(ParseCardError::Rank(s.to_string())),
+        }
+    }
+}
+
+impl fmt::Display for Rank {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Padded so cards line up when printed with a width
+        match self {
+            Rank::Ten => f.pad("10"),
+            _ => f.pad(self.to_net_name().encode_utf8(&mut [0; 4])),
+        }
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum ParseCardError {
+    Empty,
+    Rank(String),
+    Suit(String),
+}
+
+impl fmt::Display for ParseCardError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseCardError::Empty => write!(f, "No card given"),
+            ParseCardError::Rank(rank) if rank.is_empty() => write!(f, "No rank given"),
+            ParseCardError::Rank(rank) => write!(f, "Invalid rank '{}'", rank),
+            ParseCardError::Suit(suit) => write!(f, "Invalid suit '{}'", suit),
+        }
+    }
+}
+
+impl std::error::Error for ParseCardError {}
+
+impl From<ParseCardError> for io::Error {
+    fn from(e: ParseCardError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub struct Card {
-    pub value: char,
+    pub rank: Rank,
     pub suit: Suit,
 }
 
 impl Card {
     pub fn score_value(&self) -> u8 {
-        match self.value {
-            'A' => 1,
-            '2' => 2,
-            '3' => 3,
-            '4' => 4,
-            '5' => 5,
-            '6' => 6,
-            '7' => 7,
-            '8' => 8,
-            '9' => 9,
-            'T' => 10,
-            'J' => 10,
-            'Q' => 10,
-            'K' => 10,
-            _ => panic!("Bad card value!"),
-        }
+        self.rank.score_value()
     }
 
     pub fn order(&self) -> u8 {
-        match self.value {
-            'J' => 11,
-            'Q' => 12,
-            'K' => 13,
-            _ => self.score_value(),
-        }
+        self.rank.order()
     }
 
     pub fn to_net_name(&self) -> String {
-        format!(
-            "{}{}",
-            self.value,
-            match self.suit {
-                Suit::Spades => "S",
-                Suit::Hearts => "H",
-                Suit::Diamonds => "D",
-                Suit::Clubs => "C",
-            }
-        )
+        format!("{}{}", self.rank.to_net_name(), self.suit.to_net_name())
+    }
+
+    pub fn from_net_name(name: &str) -> Result<Card, ParseCardError> {
+        name.parse()
     }
 
-    pub fn from_net_name(name: String) -> Card {
-        let mut chars = name.chars();
+    // Position in a new deck, 0 to 51, suit by suit from ace to king
+    pub fn index(&self) -> u8 {
+        self.suit as u8 * 13 + self.rank as u8
+    }
 
-        let value = chars.next().unwrap();
+    pub fn from_index(index: u8) -> Option<Card> {
+        if index >= 52 {
+            return None;
+        }
 
-        let suit = match chars.next().unwrap() {
-            'S' => Suit::Spades,
-            'H' => Suit::Hearts,
-            'D' => Suit::Diamonds,
-            'C' => Suit::Clubs,
-            _ => panic!("Invalid suit!"),
-        };
+        Some(Card {
+            rank: Rank::ALL[(index % 13) as usize],
+            suit: Suit::ALL[(index / 13) as usize],
+        })
+    }
+}
+
+impl FromStr for Card {
+    type Err = ParseCardError;
 
-        Card { value, suit }
+    // Rank followed by suit, e.g. "TH", "10h" or "A♠"
+    fn from_str(s: &str) -> Result<Card, ParseCardError> {
+        let s = s.trim();
+        let suit = s.chars().last().ok_or(ParseCardError::Empty)?;
+        let rank = &s[..s.len() - suit.len_utf8()];
+
+        // A suit on its own is missing its rank, rather than missing the whole card
+        if rank.is_empty() {
+            return Err(ParseCardError::Rank(String::new()));
+        }
+
+        Ok(Card {
+            rank: rank.parse()?,
+            suit: Suit::try_from(suit)?,
+        })
     }
 }
 
-impl fmt::Display for Card {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let str = &self.value.to_string();
+impl TryFrom<&str> for Card {
+    type Error = ParseCardError;
 
-        let val = match self.value {
-            'T' => "10",
-            _ => str,
-        };
+    fn try_from(s: &str) -> Result<Card, ParseCardError> {
+        s.parse()
+    }
+}
 
-        write!(
-            f,
-            "{}{}",
-            val,
-            match self.suit {
-                Suit::Spades => "♤",
-                Suit::Hearts => "♥",
-                Suit::Diamonds => "♦",
-                Suit::Clubs => "♧",
-            }
-        )
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.rank, self.suit)
     }
 }
 
@@ -108,73 +278,120 @@ pub struct Deck {
 
 impl Deck {
     pub fn new() -> Deck {
-        let cards: Vec<Card> = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs]
-            .iter()
-            .cartesian_product("A23456789TJQK".chars())
-            .map(|(&suit, value)| Card { value, suit })
+        let cards: Vec<Card> = Suit::ALL
+            .into_iter()
+            .cartesian_product(Rank::ALL)
+            .map(|(suit, rank)| Card { rank, suit })
             .collect();
 
         Deck { cards }
     }
 
-    pub fn shuffle(&mut self, seed: String) {
+    // Puts the cards back in index order first, so the seed alone decides the deal
+    pub fn shuffle_seeded(&mut self, seed: u64) {
         assert!(
-            self.cards.len() == 52,
-            "Tried to shuffle with {} cards!",
+            self.is_complete(),
+            "Tried to shuffle an incomplete deck of {} cards!",
             self.cards.len()
         );
 
-        print!("Shuffling deck with seed '{}'", seed);
-        let s = Deck::noisy_hash_seed(seed);
-        println!(" ({})", s);
-        let mut rng = SmallRng::seed_from_u64(s);
-        self.cards.shuffle(&mut rng);
+        self.cards.sort_by_key(|card| card.index());
+        shuffle::shuffle(&mut self.cards, seed);
     }
 
-    fn noisy_hash_seed(seed: String) -> u64 {
-        let mut s = DefaultHasher::new();
-        let noise: String = rand::thread_rng()
-            .sample_iter(&rand::distributions::Alphanumeric)
-            .take(16)
-            .map(char::from)
-            .collect();
+    // Shuffles from every player's revealed secret and returns the effective seed
+    pub fn shuffle_secrets(&mut self, secrets: &[String]) -> u64 {
+        let effective = shuffle::combine_secrets(secrets);
+        self.shuffle_seeded(effective);
+        effective
+    }
+
+    // Shuffles with a generator the caller owns, e.g. for practice hands
+    pub fn shuffle_with<R: Rng>(&mut self, rng: &mut R) {
+        assert!(
+            self.is_complete(),
+            "Tried to shuffle an incomplete deck of {} cards!",
+            self.cards.len()
+        );
 
-        seed.hash(&mut s);
-        noise.hash(&mut s);
-        s.finish()
+        self.cards.shuffle(rng);
     }
 
     pub fn deal(&mut self, num: usize) -> Hand {
         Hand {
             cards: self.cards.split_off(self.cards.len() - num),
             magic: None,
+            crib: false,
         }
     }
 
-    pub fn draw_magic(&self) -> &Card {
-        self.cards.last().unwrap()
+    // Cutter lifts `position` cards off the top, the magic card is the top of the rest
+    pub fn cut(&mut self, position: usize) -> Card {
+        assert!(
+            cut_range(self.len()).contains(&position),
+            "Tried to cut {} cards from a deck of {}!",
+            position,
+            self.len()
+        );
+
+        self.cards.remove(self.cards.len() - position - 1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.cards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
     }
 
+    // Returns the hand's cards to the deck, including its magic card if it has one
     pub fn rejoin(&mut self, mut hand: Hand) {
         self.cards.append(&mut hand.cards);
+        self.cards.extend(hand.magic);
+    }
+
+    // A complete deck holds each of the 52 cards exactly once
+    pub fn is_complete(&self) -> bool {
+        self.cards.len() == 52 && self.cards.iter().all_unique()
     }
 }
 
-#[derive(Clone)]
+impl Default for Deck {
+    fn default() -> Deck {
+        Deck::new()
+    }
+}
+
+#[derive(Clone, Default)]
 pub struct Hand {
     cards: Vec<Card>,
     magic: Option<Card>,
+    crib: bool,
 }
 
 impl Hand {
     pub fn from(cards: Vec<Card>, magic: Option<Card>) -> Hand {
-        Hand { cards, magic }
+        Hand {
+            cards,
+            magic,
+            crib: false,
+        }
     }
 
     pub fn new() -> Hand {
         Hand {
             cards: Vec::new(),
             magic: None,
+            crib: false,
+        }
+    }
+
+    pub fn new_crib() -> Hand {
+        Hand {
+            cards: Vec::new(),
+            magic: None,
+            crib: true,
         }
     }
 
@@ -195,7 +412,7 @@ impl Hand {
     }
 
     pub fn combine(&mut self, other: &mut Hand) {
-        while other.len() > 0 {
+        while !other.is_empty() {
             self.push(other.remove(0))
         }
     }
@@ -204,9 +421,22 @@ impl Hand {
         self.magic = Some(magic);
     }
 
+    // The crib only scores a flush when the magic card matches too
+    pub fn set_crib(&mut self, crib: bool) {
+        self.crib = crib;
+    }
+
+    pub fn is_crib(&self) -> bool {
+        self.crib
+    }
+
     // Scores the hand for the 'Show' round, includes magic card
-    pub fn score(&self) -> u8 {
-        let mut score: u8 = 0;
+    pub fn score(&self) -> ScoreBreakdown {
+        let mut breakdown = ScoreBreakdown::new();
+        let magic = self
+            .magic
+            .as_ref()
+            .expect("Scored a hand without a magic card");
 
         let mut runs: Vec<Vec<&Card>> = Vec::new();
 
@@ -214,39 +444,31 @@ impl Hand {
         let jack: Vec<&Card> = self
             .cards
             .iter()
-            .filter(|card| card.suit == self.magic.clone().unwrap().suit)
-            .filter(|card| card.value == 'J')
+            .filter(|card| card.suit == magic.suit)
+            .filter(|card| card.rank == Rank::Jack)
             .collect();
 
         if jack.len() == 1 {
-            score += 1;
-            println!(
-                "Nob for {score}! ({}, {})",
-                jack.first().unwrap(),
-                self.magic.as_ref().unwrap()
-            );
+            breakdown.push(ScoreKind::Nob, vec![jack[0].clone(), magic.clone()], 1);
         }
 
-        let mut full_hand = vec![self.magic.clone().unwrap()];
+        let mut full_hand = vec![magic.clone()];
         full_hand.extend_from_slice(&self.cards);
-        full_hand.sort_by(|a, b| a.order().cmp(&b.order()));
+        full_hand.sort_by_key(|card| card.order());
 
-        for perm in (2..=full_hand.len())
-            .collect::<Vec<usize>>()
-            .iter()
-            .map(|len| full_hand.iter().combinations(*len))
-            .flatten()
-        {
+        for perm in (2..=full_hand.len()).flat_map(|len| full_hand.iter().combinations(len)) {
             // Pairs
-            if perm.len() == 2 && (perm[0].value == perm[1].value) {
-                score += 2;
-                println!("Pair for {score}! ({}, {})", perm[0], perm[1]);
+            if perm.len() == 2 && (perm[0].rank == perm[1].rank) {
+                breakdown.push(ScoreKind::Pair, perm.iter().copied().cloned().collect(), 2);
             }
 
             // Fifteens
             if perm.iter().map(|card| card.score_value()).sum::<u8>() == 15 {
-                score += 2;
-                println!("Fifteen for {score}! ({})", perm.iter().join(", "));
+                breakdown.push(
+                    ScoreKind::Fifteen,
+                    perm.iter().copied().cloned().collect(),
+                    2,
+                );
             }
 
             // Runs
@@ -265,46 +487,45 @@ impl Hand {
 
         // Score runs
         for run in runs {
-            score += run.len() as u8;
-            println!(
-                "Run of {} for {score}! ({})",
-                run.len(),
-                run.iter().join(", ")
-            );
+            let points = run.len() as u8;
+            breakdown.push(ScoreKind::Run, run.into_iter().cloned().collect(), points);
         }
 
-        if score == 0 {
-            println!("0 points!");
+        // Flush, hands score one a card without the magic card but the crib needs it too
+        if self.cards.len() >= 3 && self.cards.iter().map(|card| card.suit).all_equal() {
+            let mut flush = self.cards.clone();
+
+            if magic.suit == flush[0].suit {
+                flush.push(magic.clone());
+            }
+
+            if !self.crib || flush.len() > self.cards.len() {
+                let points = flush.len() as u8;
+                breakdown.push(ScoreKind::Flush, flush, points);
+            }
         }
 
-        score
+        breakdown
     }
 
     pub fn len(&self) -> usize {
         self.cards.len()
     }
 
+    pub fn is_empty(&self) -> bool {
+        self.cards.is_empty()
+    }
+
     fn format_card(lines: &mut Vec<String>, card: &Card) {
         let second_line = lines.get(1).unwrap();
 
-        let new = if card.value == 'T' {
-            second_line.replacen("xz", "10", 1)
-        } else {
-            second_line.replacen("xz", &format!("{} ", card.value), 1)
-        };
+        let new = second_line.replacen("xz", &format!("{:<2}", card.rank), 1);
 
         lines.remove(1);
         lines.insert(1, new);
 
         let fourth_line = lines.get(3).unwrap();
-        let suit = match card.suit {
-            Suit::Spades => "♤",
-            Suit::Hearts => "♥",
-            Suit::Diamonds => "♦",
-            Suit::Clubs => "♧",
-        };
-
-        let new = fourth_line.replacen("y", suit, 1);
+        let new = fourth_line.replacen("y", &card.suit.to_string(), 1);
         lines.remove(3);
         lines.insert(3, new);
     }
@@ -344,8 +565,8 @@ impl Hand {
 
                 Hand::format_card(&mut magic_lines, magic);
 
-                for i in 0..5 {
-                    lines[i].push_str(magic_lines[i].as_str());
+                for (line, magic_line) in lines.iter_mut().zip(magic_lines) {
+                    line.push_str(&magic_line);
                 }
             }
         }
@@ -358,6 +579,23 @@ impl Hand {
     pub fn magic(&self) -> Option<&Card> {
         self.magic.as_ref()
     }
+
+    // Percent of all 4 card hands (or cribs) with a magic card that score the same or less,
+    // None for other sizes of hand since the tables only cover 4 cards
+    pub fn percentile(&self) -> Option<f64> {
+        self.has_stats()
+            .then(|| stats::percentile(self.score().total(), self.crib))
+    }
+
+    // Percent of all 4 card hands (or cribs) with a magic card that score the same or more
+    pub fn top_percent(&self) -> Option<f64> {
+        self.has_stats()
+            .then(|| stats::top_percent(self.score().total(), self.crib))
+    }
+
+    fn has_stats(&self) -> bool {
+        self.cards.len() == 4 && self.magic.is_some()
+    }
 }
 
 impl fmt::Display for Hand {
@@ -372,6 +610,126 @@ impl fmt::Display for Hand {
     }
 }
 
+// How badly a player lost the game
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Skunk {
+    None,
+    Single, // Finished below the rules' skunk line, 91 in a standard game
+    Double, // Finished below the double skunk line, 61 in a standard game
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum ScoreKind {
+    Fifteen,
+    Pair,
+    Run,
+    Flush,
+    Nob,
+    Heels,
+    ThirtyOne,
+    Go,
+    LastCard,
+}
+
+// A single scoring combination, e.g. one fifteen or one run
+#[derive(Clone, Debug)]
+pub struct ScoreEvent {
+    pub kind: ScoreKind,
+    pub cards: Vec<Card>,
+    pub points: u8,
+    pub total: u8, // Running total including this event
+}
+
+impl fmt::Display for ScoreEvent {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self.kind {
+            ScoreKind::Fifteen => String::from("Fifteen"),
+            ScoreKind::Pair => match self.cards.len() {
+                3 => String::from("Triplet"),
+                4 => String::from("Quadruplet"),
+                _ => String::from("Pair"),
+            },
+            ScoreKind::Run => format!("Run of {}", self.cards.len()),
+            ScoreKind::Flush => format!("Flush of {}", self.cards.len()),
+            ScoreKind::Nob => String::from("Nob"),
+            ScoreKind::Heels => String::from("His heels"),
+            ScoreKind::ThirtyOne => String::from("31"),
+            ScoreKind::Go => String::from("Go"),
+            ScoreKind::LastCard => String::from("Last card"),
+        };
+
+        write!(f, "{} for {}!", name, self.total)?;
+
+        if !self.cards.is_empty() {
+            write!(f, " ({})", self.cards.iter().join(", "))?;
+        }
+
+        Ok(())
+    }
+}
+
+// Every scoring combination found in a hand, in the order they were counted
+#[derive(Clone, Debug, Default)]
+pub struct ScoreBreakdown {
+    events: Vec<ScoreEvent>,
+}
+
+impl ScoreBreakdown {
+    pub fn new() -> ScoreBreakdown {
+        ScoreBreakdown { events: Vec::new() }
+    }
+
+    pub fn push(&mut self, kind: ScoreKind, cards: Vec<Card>, points: u8) {
+        let total = self.total() + points;
+
+        self.events.push(ScoreEvent {
+            kind,
+            cards,
+            points,
+            total,
+        });
+    }
+
+    pub fn events(&self) -> &[ScoreEvent] {
+        &self.events
+    }
+
+    pub fn total(&self) -> u8 {
+        self.events.last().map_or(0, |event| event.total)
+    }
+}
+
+// Each event on its own line, then the total
+impl fmt::Display for ScoreBreakdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for event in &self.events {
+            writeln!(f, "{}", event)?;
+        }
+
+        if self.total() == 0 {
+            write!(f, "0 points!")
+        } else {
+            write!(f, "{} point hand!", self.total())
+        }
+    }
+}
+
+// Dealer scores his heels when the magic card is a jack
+pub fn score_cut(magic: &Card) -> ScoreBreakdown {
+    let mut breakdown = ScoreBreakdown::new();
+
+    if magic.rank == Rank::Jack {
+        breakdown.push(ScoreKind::Heels, vec![magic.clone()], 2);
+    }
+
+    breakdown
+}
+
+// Positions a deck of the given size can be cut at, leaving at least 4 cards in each part
+pub fn cut_range(deck_size: usize) -> RangeInclusive<usize> {
+    4..=deck_size.saturating_sub(4)
+}
+
 // Takes a sorted sliced of cards and returns true if they are a run
 pub fn is_run(cards: &[&Card]) -> bool {
     if cards.len() < 3 {
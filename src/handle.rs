@@ -3,9 +3,12 @@
 use crate::frame::Frame;
 use crate::game::Card;
 use crate::game::Hand;
+use crate::game::Skunk;
+use crate::rules::RuleSet;
 use bytes::{BufMut, BytesMut};
 use std::io::{self, BufRead, BufReader, Write};
 use std::net::TcpStream;
+use std::str::FromStr;
 
 pub struct Handle {
     reader: BufReader<TcpStream>,
@@ -16,7 +19,7 @@ impl Handle {
     pub fn new(stream: TcpStream) -> Handle {
         Handle {
             reader: BufReader::new(stream.try_clone().unwrap()),
-            stream: stream,
+            stream,
         }
     }
 
@@ -36,12 +39,15 @@ impl Handle {
         let mut buffer = BytesMut::with_capacity(256);
 
         match frame {
-            Frame::Name(name) => {
+            Frame::Name(name, team) => {
+                // Team as one digit, 0 for no preference and then from 1
                 buffer.put_u8(0x1);
+                buffer.put(team.map_or(0, |team| team + 1).to_string().as_bytes());
                 buffer.put(name.as_bytes());
             }
-            Frame::Start(names) => {
+            Frame::Start(names, rules) => {
                 buffer.put_u8(0x2);
+                buffer.put(format!("{},", rules).as_bytes());
 
                 for name in names {
                     buffer.put(format!("{},", name).as_bytes());
@@ -49,6 +55,7 @@ impl Handle {
             }
             Frame::Hand(hand) => {
                 buffer.put_u8(0x3);
+                buffer.put_u8(if hand.is_crib() { 0x1 } else { 0x0 });
 
                 if let Some(magic) = hand.magic() {
                     buffer.put_u8(0x1);
@@ -66,21 +73,73 @@ impl Handle {
                 buffer.put_u8(0x4);
                 buffer.put(card.to_net_name().as_bytes());
             }
-            Frame::Play(card, out) => {
+            Frame::Play(card) => {
                 buffer.put_u8(0x5);
-                buffer.put_u8(if *out { 0x1 } else { 0x0 });
                 if let Some(card) = card {
                     buffer.put(card.to_net_name().as_bytes());
                 } else {
                     buffer.put_slice(b"go");
                 }
             }
-            Frame::RoundDone => {
-                buffer.put_u8(0x6);
-            }
-            Frame::Seed(seed) => {
+            Frame::Commit(commit) => {
                 buffer.put_u8(0x7);
-                buffer.put(seed.as_bytes());
+                buffer.put(commit.as_bytes());
+            }
+            Frame::Scores(scores) => {
+                buffer.put_u8(0x8);
+
+                for score in scores {
+                    buffer.put(format!("{},", score).as_bytes());
+                }
+            }
+            Frame::GameOver(winner, scores, skunks) => {
+                // 0x9 to 0xD are whitespace and would be trimmed from the line
+                buffer.put_u8(0xE);
+                buffer.put(format!("{},", winner).as_bytes());
+
+                for (score, skunk) in scores.iter().zip(skunks) {
+                    let skunk = match skunk {
+                        Skunk::None => 0,
+                        Skunk::Single => 1,
+                        Skunk::Double => 2,
+                    };
+
+                    buffer.put(format!("{}:{},", score, skunk).as_bytes());
+                }
+            }
+            Frame::Error(message) => {
+                buffer.put_u8(0xF);
+                buffer.put(message.replace('\n', " ").as_bytes());
+            }
+            Frame::Cut(position) => {
+                buffer.put_u8(0x10);
+                buffer.put(position.to_string().as_bytes());
+            }
+            Frame::Claim(points) => {
+                buffer.put_u8(0x11);
+                buffer.put(points.to_string().as_bytes());
+            }
+            Frame::Claimed(player, points) => {
+                buffer.put_u8(0x12);
+                buffer.put(format!("{},{},", player, points).as_bytes());
+            }
+            Frame::Commits(commits) => {
+                buffer.put_u8(0x13);
+
+                for commit in commits {
+                    buffer.put(format!("{},", commit).as_bytes());
+                }
+            }
+            Frame::Reveal(secret) => {
+                buffer.put_u8(0x14);
+                buffer.put(secret.as_bytes());
+            }
+            Frame::Reveals(secrets) => {
+                buffer.put_u8(0x15);
+
+                for secret in secrets {
+                    buffer.put(format!("{},", secret).as_bytes());
+                }
             }
         }
 
@@ -93,53 +152,205 @@ impl Handle {
 }
 
 fn parse_frame(buffer: &str) -> Result<Option<Frame>, io::Error> {
-    if buffer.is_empty() {
-        return Ok(None);
-    }
+    let kind = match buffer.as_bytes().first() {
+        Some(kind) => *kind,
+        None => return Ok(None),
+    };
 
-    match buffer.as_bytes().get(0).unwrap() {
-        0x1 => Ok(Some(Frame::Name(buffer[1..].to_string()))),
-        0x2 => Ok(Some(Frame::Start(
-            buffer[1..]
-                .strip_suffix(',')
-                .unwrap()
-                .split(',')
-                .map(|str| String::from(str))
-                .collect(),
-        ))),
+    let body = buffer.get(1..).ok_or_else(invalid_data)?;
+
+    match kind {
+        0x1 => {
+            let team = body.get(..1).ok_or_else(invalid_data)?;
+            let name = body.get(1..).ok_or_else(invalid_data)?;
+
+            let team = match parse_number::<usize>(team)? {
+                0 => None,
+                team => Some(team - 1),
+            };
+
+            Ok(Some(Frame::Name(name.to_string(), team)))
+        }
+        0x2 => {
+            // Rules, then the names
+            let fields = split_list(body)?;
+            let (rules, names) = fields.split_first().ok_or_else(invalid_data)?;
+
+            Ok(Some(Frame::Start(
+                names.iter().map(|name| name.to_string()).collect(),
+                rules.parse::<RuleSet>()?,
+            )))
+        }
         0x3 => {
-            let magic = if buffer[1..2].as_bytes().get(0).unwrap() == &0x1 {
-                Some(Card::from_net_name(buffer[2..4].to_string()))
+            // Crib flag, magic flag, magic card, then the cards
+            let flags = body.as_bytes();
+
+            if flags.len() < 4 {
+                return Err(invalid_data());
+            }
+
+            let magic = if flags[1] == 0x1 {
+                Some(Card::from_net_name(
+                    body.get(2..4).ok_or_else(invalid_data)?,
+                )?)
             } else {
                 None
             };
 
-            Ok(Some(Frame::Hand(Hand::from(
-                buffer[4..]
-                    .strip_suffix(',')
-                    .unwrap()
-                    .split(',')
-                    .map(|str| Card::from_net_name(str.to_string()))
-                    .collect(),
-                magic,
-            ))))
+            let cards = parse_cards(body.get(4..).ok_or_else(invalid_data)?)?;
+            let mut hand = Hand::from(cards, magic);
+            hand.set_crib(flags[0] == 0x1);
+
+            Ok(Some(Frame::Hand(hand)))
         }
-        0x4 => Ok(Some(Frame::Card(Card::from_net_name(
-            buffer[1..].to_string(),
-        )))),
+        0x4 => Ok(Some(Frame::Card(Card::from_net_name(body)?))),
         0x5 => {
-            let card = if buffer[2..].eq("go") {
+            let card = if body == "go" {
                 None
             } else {
-                Some(Card::from_net_name(buffer[2..].to_string()))
+                Some(Card::from_net_name(body)?)
             };
 
-            let out = buffer[1..2].as_bytes().get(0).unwrap() == &0x1;
+            Ok(Some(Frame::Play(card)))
+        }
+        0x7 => Ok(Some(Frame::Commit(body.to_string()))),
+        0x8 => Ok(Some(Frame::Scores(
+            split_list(body)?
+                .into_iter()
+                .map(parse_number)
+                .collect::<Result<Vec<u8>, _>>()?,
+        ))),
+        0xE => {
+            let fields = split_list(body)?;
+            let (winner, fields) = fields.split_first().ok_or_else(invalid_data)?;
+
+            let winner = parse_number(winner)?;
+            let mut scores = Vec::new();
+            let mut skunks = Vec::new();
+
+            for field in fields {
+                let (score, skunk) = field.split_once(':').ok_or_else(invalid_data)?;
+
+                scores.push(parse_number(score)?);
+                skunks.push(match skunk {
+                    "0" => Skunk::None,
+                    "1" => Skunk::Single,
+                    "2" => Skunk::Double,
+                    _ => return Err(invalid_data()),
+                });
+            }
 
-            Ok(Some(Frame::Play(card, out)))
+            // Winner has to be one of the players scored
+            if winner >= scores.len() {
+                return Err(invalid_data());
+            }
+
+            Ok(Some(Frame::GameOver(winner, scores, skunks)))
         }
-        0x6 => Ok(Some(Frame::RoundDone)),
-        0x7 => Ok(Some(Frame::Seed(buffer[1..].to_string()))),
+        0xF => Ok(Some(Frame::Error(body.to_string()))),
+        0x10 => Ok(Some(Frame::Cut(parse_number(body)?))),
+        0x11 => Ok(Some(Frame::Claim(parse_number(body)?))),
+        0x12 => match split_list(body)?.as_slice() {
+            [player, points] => Ok(Some(Frame::Claimed(
+                parse_number(player)?,
+                parse_number(points)?,
+            ))),
+            _ => Err(invalid_data()),
+        },
+        0x13 => Ok(Some(Frame::Commits(
+            split_list(body)?.into_iter().map(String::from).collect(),
+        ))),
+        0x14 => Ok(Some(Frame::Reveal(body.to_string()))),
+        0x15 => Ok(Some(Frame::Reveals(
+            split_list(body)?.into_iter().map(String::from).collect(),
+        ))),
         _ => Err(io::ErrorKind::InvalidData.into()),
     }
 }
+
+// Lists are sent with a comma after every item
+fn split_list(body: &str) -> Result<Vec<&str>, io::Error> {
+    if body.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    Ok(body
+        .strip_suffix(',')
+        .ok_or_else(invalid_data)?
+        .split(',')
+        .collect())
+}
+
+fn parse_cards(body: &str) -> Result<Vec<Card>, io::Error> {
+    split_list(body)?
+        .into_iter()
+        .map(|name| Ok(Card::from_net_name(name)?))
+        .collect()
+}
+
+fn parse_number<T: FromStr>(str: &str) -> Result<T, io::Error> {
+    str.parse().map_err(|_| invalid_data())
+}
+
+fn invalid_data() -> io::Error {
+    io::ErrorKind::InvalidData.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Frames from the network are untrusted, so bad ones have to fail rather than panic
+    #[test]
+    fn malformed_frames_fail() {
+        let frames = [
+            "\u{3}",
+            "\u{3}\u{0}",
+            "\u{3}\u{0}\u{1}A",
+            "\u{3}\u{0}\u{1}♠AS,",
+            "\u{3}\u{0}\u{0}0é",
+            "\u{3}\u{0}\u{1}XXAS,",
+            "\u{3}\u{0}\u{0}00AS",
+            "é",
+            "\u{1}",
+            "\u{1}éname",
+            "\u{2}",
+            "\u{4}",
+            "\u{E}",
+            "\u{E}0,",
+            "\u{E}2,120:0,60:1,",
+            "\u{E}0,120,60:1,",
+            "\u{E}0,120:3,",
+            "\u{E}x,120:0,",
+            "\u{E}0,120:0",
+            "\u{12}1,",
+            "\u{7F}",
+        ];
+
+        for frame in frames {
+            assert!(parse_frame(frame).is_err(), "{:?} parsed", frame);
+        }
+    }
+
+    #[test]
+    fn frames_parse() {
+        assert!(parse_frame("").unwrap().is_none());
+
+        match parse_frame("\u{3}\u{1}\u{1}5HAS,10D,") {
+            Ok(Some(Frame::Hand(hand))) => {
+                assert!(hand.is_crib());
+                assert_eq!(hand.magic(), Some(&"5H".parse().unwrap()));
+                assert_eq!(hand.cards().len(), 2);
+            }
+            _ => panic!("Hand didn't parse"),
+        }
+
+        match parse_frame("\u{E}1,120:0,121:0,") {
+            Ok(Some(Frame::GameOver(1, scores, skunks))) => {
+                assert_eq!(scores, [120, 121]);
+                assert_eq!(skunks, [Skunk::None, Skunk::None]);
+            }
+            _ => panic!("GameOver didn't parse"),
+        }
+    }
+}
@@ -6,3 +6,20 @@ pub use handle::Handle;
 
 pub mod game;
 pub use game::Deck;
+
+pub mod shuffle;
+
+pub mod rules;
+pub use rules::RuleSet;
+
+pub mod cardset;
+pub use cardset::CardSet;
+
+pub mod score;
+
+pub mod analysis;
+
+pub mod stats;
+
+pub mod pegging;
+pub use pegging::PeggingState;
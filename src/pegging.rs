@@ -0,0 +1,250 @@
+use crate::game::{is_run, Card, Hand, ScoreBreakdown, ScoreKind};
+use itertools::Itertools;
+use std::fmt;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum Status {
+    Playing, // Can still play on the current count
+    Go,      // Said go, waits for the count to reset
+    Out,     // Played all of their cards
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum PlayError {
+    Finished,  // Every card has already been played
+    OverCount, // Card would take the count past 31
+    NoCards,   // Player has already played all of their cards
+    NotInHand, // Card isn't one the player is holding
+    CanPlay,   // Player said go while holding a playable card
+}
+
+impl fmt::Display for PlayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlayError::Finished => write!(f, "Play is already finished"),
+            PlayError::OverCount => write!(f, "Card would take the count past 31"),
+            PlayError::NoCards => write!(f, "No cards left to play"),
+            PlayError::NotInHand => write!(f, "Card is not in your hand"),
+            PlayError::CanPlay => write!(f, "Can't say go while holding a playable card"),
+        }
+    }
+}
+
+impl std::error::Error for PlayError {}
+
+// What happened as the result of a single play
+pub struct PlayResult {
+    pub player: usize, // Player credited with the breakdown, not always the one who played
+    pub breakdown: ScoreBreakdown,
+    pub reset: bool, // Count went back to zero after this play
+}
+
+// Tracks the count, the cards played on it and whose turn it is during the play
+pub struct PeggingState {
+    count: u8,
+    history: Vec<Card>,
+    status: Vec<Status>,
+    remaining: Vec<usize>,
+    current: usize,
+    last_player: Option<usize>,
+}
+
+impl PeggingState {
+    pub fn new(num_players: usize, first_player: usize, cards_each: usize) -> PeggingState {
+        PeggingState {
+            count: 0,
+            history: Vec::new(),
+            status: vec![Status::Playing; num_players],
+            remaining: vec![cards_each; num_players],
+            current: first_player,
+            last_player: None,
+        }
+    }
+
+    pub fn count(&self) -> u8 {
+        self.count
+    }
+
+    // Cards played since the count was last reset
+    pub fn history(&self) -> &[Card] {
+        &self.history
+    }
+
+    pub fn status(&self, player: usize) -> Status {
+        self.status[player]
+    }
+
+    pub fn current_player(&self) -> usize {
+        self.current
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.status.iter().all(|status| status == &Status::Out)
+    }
+
+    // Cards from the hand that can be played without going past 31
+    pub fn legal_moves(&self, hand: &Hand) -> Vec<Card> {
+        hand.cards()
+            .iter()
+            .filter(|card| self.count + card.score_value() <= 31)
+            .cloned()
+            .collect()
+    }
+
+    // Checks a play against the cards the current player is still holding
+    pub fn validate(&self, hand: &Hand, card: Option<&Card>) -> Result<(), PlayError> {
+        if self.is_finished() {
+            return Err(PlayError::Finished);
+        }
+
+        match card {
+            Some(card) if !hand.cards().contains(card) => Err(PlayError::NotInHand),
+            Some(card) if self.count + card.score_value() > 31 => Err(PlayError::OverCount),
+            None if !self.legal_moves(hand).is_empty() => Err(PlayError::CanPlay),
+            _ => Ok(()),
+        }
+    }
+
+    // Plays a card for the current player, or says go if None
+    pub fn play(&mut self, card: Option<Card>) -> Result<PlayResult, PlayError> {
+        if self.is_finished() {
+            return Err(PlayError::Finished);
+        }
+
+        let player = self.current;
+
+        if self.remaining[player] == 0 {
+            return Err(PlayError::NoCards);
+        }
+
+        let mut result = PlayResult {
+            player,
+            breakdown: ScoreBreakdown::new(),
+            reset: false,
+        };
+
+        if let Some(card) = card {
+            if self.count + card.score_value() > 31 {
+                return Err(PlayError::OverCount);
+            }
+
+            self.count += card.score_value();
+            self.history.push(card);
+            self.remaining[player] -= 1;
+            self.last_player = Some(player);
+
+            if self.remaining[player] == 0 {
+                self.status[player] = Status::Out;
+            }
+
+            result.breakdown = score_play(&self.history, self.count);
+
+            if self.count == 31 {
+                self.reset();
+                result.reset = true;
+                return Ok(result);
+            }
+        } else {
+            self.status[player] = Status::Go;
+        }
+
+        if self.status.iter().any(|status| status == &Status::Playing) {
+            self.current = self.next_playing(player);
+            return Ok(result);
+        }
+
+        // Nobody can play, last player to lay a card scores for go or last card
+        if let Some(last_player) = self.last_player {
+            result.player = last_player;
+
+            let kind = if self.is_finished() {
+                ScoreKind::LastCard
+            } else {
+                ScoreKind::Go
+            };
+
+            result.breakdown.push(kind, Vec::new(), 1);
+        }
+
+        self.reset();
+        result.reset = true;
+
+        Ok(result)
+    }
+
+    fn reset(&mut self) {
+        self.count = 0;
+        self.history.clear();
+
+        for (status, remaining) in self.status.iter_mut().zip(&self.remaining) {
+            *status = if *remaining == 0 {
+                Status::Out
+            } else {
+                Status::Playing
+            };
+        }
+
+        // Player after the last one to lay a card leads the new count
+        if !self.is_finished() {
+            let last_player = self.last_player.unwrap_or(self.current);
+            self.current = self.next_playing(last_player);
+        }
+    }
+
+    fn next_playing(&self, player: usize) -> usize {
+        let len = self.status.len();
+
+        (1..=len)
+            .map(|offset| (player + offset) % len)
+            .find(|index| self.status[*index] == Status::Playing)
+            .expect("No player can play")
+    }
+}
+
+// Scores the card just played given the cards played since the last reset
+pub fn score_play(history: &[Card], count: u8) -> ScoreBreakdown {
+    let mut breakdown = ScoreBreakdown::new();
+
+    // Longest run formed by the most recent cards
+    let history_len = history.len();
+    if history_len > 2 {
+        let longest_run = (0..history_len - 2)
+            .map(|num_drop| {
+                history
+                    .iter()
+                    .dropping(num_drop)
+                    .sorted_by_key(|card| card.order())
+                    .collect::<Vec<&Card>>()
+            })
+            .find(|sorted_subset| is_run(sorted_subset));
+
+        if let Some(run) = longest_run {
+            let points = run.len() as u8;
+            breakdown.push(ScoreKind::Run, run.into_iter().cloned().collect(), points);
+        }
+    }
+
+    // Pair, triplet or quadruplet with the most recent cards
+    let last = history.last().expect("Scored a play with no cards");
+    let matching: Vec<Card> = history
+        .iter()
+        .rev()
+        .take_while(|card| card.rank == last.rank)
+        .cloned()
+        .collect();
+
+    if matching.len() >= 2 {
+        let points = (matching.len() * (matching.len() - 1)) as u8;
+        breakdown.push(ScoreKind::Pair, matching, points);
+    }
+
+    if count == 15 {
+        breakdown.push(ScoreKind::Fifteen, history.to_vec(), 2);
+    }
+
+    if count == 31 {
+        breakdown.push(ScoreKind::ThirtyOne, history.to_vec(), 2);
+    }
+
+    breakdown
+}
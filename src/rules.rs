@@ -0,0 +1,362 @@
+// The rules a game is played by, chosen on the server and sent to every client at the start
+//
+// Rules are written as a preset name followed by any changes, separated by spaces or commas,
+// e.g. "five-card,muggins,target=91". Display writes every rule out so clients don't need to
+// know the presets, and what it writes parses back to the same rules.
+use crate::game::{Deck, Hand, Skunk};
+use std::fmt;
+use std::io;
+use std::str::FromStr;
+
+// Fewest cards that can be left after the deal, so the cut leaves 4 on either side
+const MIN_STOCK: usize = 8;
+
+// Every deal leaves a crib of 4 cards, topped up from the stock when the discards fall short
+pub const CRIB_SIZE: usize = 4;
+
+// How the cards go out for a number of players
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Deal {
+    pub players: usize,
+    pub dealt: usize,      // Cards dealt to each player
+    pub discard: usize,    // Cards each player puts in the crib
+    pub from_stock: usize, // Cards the dealer puts in the crib from the top of the deck
+}
+
+impl Deal {
+    // Cards each player keeps for the play and the show
+    pub fn kept(&self) -> usize {
+        self.dealt - self.discard
+    }
+
+    pub fn crib_size(&self) -> usize {
+        self.players * self.discard + self.from_stock
+    }
+
+    // Deals hands from the first player round to the dealer, then the cards for the crib from
+    // the stock. Server and clients deal this way so clients can check the deal from the secrets
+    pub fn deal_hands(&self, deck: &mut Deck, first_player: usize) -> (Vec<Hand>, Hand) {
+        let mut hands = vec![Hand::new(); self.players];
+
+        for offset in 0..self.players {
+            hands[(first_player + offset) % self.players] = deck.deal(self.dealt);
+        }
+
+        let stock = deck.deal(self.from_stock);
+
+        (hands, stock)
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct RuleSet {
+    pub name: String,
+    pub target: u8,         // Points needed to win
+    pub skunk: u8,          // Losers below this are skunked
+    pub double_skunk: u8,   // Losers below this are double skunked
+    pub muggins: bool,      // Players count their own hands and others can claim missed points
+    pub pone_bonus: u8,     // Points the pone gets before the first deal, for going second
+    pub partners: bool,     // Four players play in pairs, partners sit opposite and share a score
+    pub reproducible: bool, // Deals come from a seed the server chose, not everyone's secrets
+    pub deals: Vec<Deal>,   // Deal for each supported number of players
+}
+
+impl RuleSet {
+    pub const PRESETS: [&'static str; 2] = ["standard", "five-card"];
+
+    // Six card cribbage to 121
+    pub fn standard() -> RuleSet {
+        RuleSet {
+            name: String::from("standard"),
+            target: 121,
+            skunk: 91,
+            double_skunk: 61,
+            muggins: false,
+            pone_bonus: 0,
+            partners: true,
+            reproducible: false,
+            deals: vec![
+                Deal {
+                    players: 2,
+                    dealt: 6,
+                    discard: 2,
+                    from_stock: 0,
+                },
+                // Three players leave a card short, so the crib gets one from the stock
+                Deal {
+                    players: 3,
+                    dealt: 5,
+                    discard: 1,
+                    from_stock: 1,
+                },
+                Deal {
+                    players: 4,
+                    dealt: 5,
+                    discard: 1,
+                    from_stock: 0,
+                },
+            ],
+        }
+    }
+
+    // The classic two player game to 61, keeping 3 of 5 cards, the pone starts with 3 for last
+    pub fn five_card() -> RuleSet {
+        RuleSet {
+            name: String::from("five-card"),
+            target: 61,
+            skunk: 31,
+            double_skunk: 0,
+            muggins: false,
+            pone_bonus: 3,
+            partners: false,
+            reproducible: false,
+            deals: vec![Deal {
+                players: 2,
+                dealt: 5,
+                discard: 2,
+                from_stock: 0,
+            }],
+        }
+    }
+
+    pub fn preset(name: &str) -> Option<RuleSet> {
+        match name {
+            "standard" => Some(RuleSet::standard()),
+            "five-card" => Some(RuleSet::five_card()),
+            _ => None,
+        }
+    }
+
+    // None if the game can't be played with this many players
+    pub fn deal(&self, num_players: usize) -> Option<&Deal> {
+        self.deals.iter().find(|deal| deal.players == num_players)
+    }
+
+    // Team a seat plays for, players 0 and 2 against 1 and 3 in a partnership game
+    pub fn team(&self, player: usize, num_players: usize) -> usize {
+        if self.partners && num_players == 4 {
+            player % 2
+        } else {
+            player
+        }
+    }
+
+    // Every seat on the same team as the player, including the player, in seat order
+    pub fn team_seats(&self, player: usize, num_players: usize) -> Vec<usize> {
+        let team = self.team(player, num_players);
+
+        (0..num_players)
+            .filter(|seat| self.team(*seat, num_players) == team)
+            .collect()
+    }
+
+    pub fn num_teams(&self, num_players: usize) -> usize {
+        if self.partners && num_players == 4 {
+            2
+        } else {
+            num_players
+        }
+    }
+
+    pub fn skunk(&self, score: u8) -> Skunk {
+        if score < self.double_skunk {
+            Skunk::Double
+        } else if score < self.skunk {
+            Skunk::Single
+        } else {
+            Skunk::None
+        }
+    }
+
+    fn set_deal(&mut self, deal: Deal) {
+        self.deals.retain(|d| d.players != deal.players);
+        self.deals.push(deal);
+        self.deals.sort_by_key(|d| d.players);
+    }
+
+    fn check(&self) -> Result<(), ParseRulesError> {
+        if self.target == 0 {
+            return Err(ParseRulesError::Invalid(String::from(
+                "Target must be above 0",
+            )));
+        }
+
+        if self.pone_bonus >= self.target {
+            return Err(ParseRulesError::Invalid(String::from(
+                "Pone bonus must be below the target",
+            )));
+        }
+
+        if self.double_skunk > self.skunk || self.skunk > self.target {
+            return Err(ParseRulesError::Invalid(String::from(
+                "Skunk lines must be in order and no higher than the target",
+            )));
+        }
+
+        for deal in &self.deals {
+            // Someone has to hold cards, and the stock has to be big enough to cut, see cut_range
+            let used = deal
+                .players
+                .checked_mul(deal.dealt)
+                .and_then(|dealt| dealt.checked_add(deal.from_stock));
+
+            if deal.players == 0
+                || deal.discard >= deal.dealt
+                || used.is_none_or(|used| used > 52 - MIN_STOCK)
+            {
+                return Err(ParseRulesError::Invalid(format!(
+                    "Can't deal {} cards to {} players",
+                    deal.dealt, deal.players
+                )));
+            }
+
+            // Safe from overflow now the deal is known to fit in the deck
+            if deal.crib_size() != CRIB_SIZE {
+                return Err(ParseRulesError::Invalid(format!(
+                    "Deal for {} players makes a crib of {} cards, not {}",
+                    deal.players,
+                    deal.crib_size(),
+                    CRIB_SIZE
+                )));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> RuleSet {
+        RuleSet::standard()
+    }
+}
+
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub enum ParseRulesError {
+    Preset(String),
+    Rule(String),
+    Invalid(String),
+}
+
+impl fmt::Display for ParseRulesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseRulesError::Preset(name) => write!(
+                f,
+                "Unknown rules '{}', expected one of {}",
+                name,
+                RuleSet::PRESETS.join(", ")
+            ),
+            ParseRulesError::Rule(rule) => write!(f, "Invalid rule '{}'", rule),
+            ParseRulesError::Invalid(reason) => write!(f, "{}", reason),
+        }
+    }
+}
+
+impl std::error::Error for ParseRulesError {}
+
+impl From<ParseRulesError> for io::Error {
+    fn from(e: ParseRulesError) -> io::Error {
+        io::Error::new(io::ErrorKind::InvalidData, e)
+    }
+}
+
+impl FromStr for RuleSet {
+    type Err = ParseRulesError;
+
+    fn from_str(s: &str) -> Result<RuleSet, ParseRulesError> {
+        let mut words = s
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|word| !word.is_empty())
+            .peekable();
+
+        // Changes on their own start from the standard rules
+        let mut rules = match words.peek() {
+            Some(name)
+                if !name.contains('=')
+                    && !["muggins", "partners", "reproducible"].contains(name) =>
+            {
+                let rules = RuleSet::preset(name)
+                    .ok_or_else(|| ParseRulesError::Preset(name.to_string()))?;
+                words.next();
+                rules
+            }
+            _ => RuleSet::standard(),
+        };
+
+        for word in words {
+            let invalid = || ParseRulesError::Rule(word.to_string());
+            let (key, value) = word.split_once('=').unwrap_or((word, ""));
+            let number = || value.parse::<u8>().map_err(|_| invalid());
+
+            match key {
+                "muggins" if value.is_empty() => rules.muggins = true,
+                "muggins" => rules.muggins = value.parse().map_err(|_| invalid())?,
+                "partners" if value.is_empty() => rules.partners = true,
+                "partners" => rules.partners = value.parse().map_err(|_| invalid())?,
+                "reproducible" if value.is_empty() => rules.reproducible = true,
+                "reproducible" => rules.reproducible = value.parse().map_err(|_| invalid())?,
+                "target" => rules.target = number()?,
+                "skunk" => rules.skunk = number()?,
+                "double-skunk" => rules.double_skunk = number()?,
+                "pone-bonus" => rules.pone_bonus = number()?,
+                "deal" => {
+                    // Players, cards dealt, cards discarded and optionally cards from the
+                    // stock, which otherwise fill the crib up, e.g. deal=2:6:2 or deal=3:5:1:1
+                    let fields = value
+                        .split(':')
+                        .map(|field| field.parse::<usize>().map_err(|_| invalid()))
+                        .collect::<Result<Vec<usize>, _>>()?;
+
+                    match fields.as_slice() {
+                        [players, dealt, discard] => rules.set_deal(Deal {
+                            players: *players,
+                            dealt: *dealt,
+                            discard: *discard,
+                            from_stock: CRIB_SIZE.saturating_sub(players.saturating_mul(*discard)),
+                        }),
+                        [players, dealt, discard, from_stock] => rules.set_deal(Deal {
+                            players: *players,
+                            dealt: *dealt,
+                            discard: *discard,
+                            from_stock: *from_stock,
+                        }),
+                        _ => return Err(invalid()),
+                    }
+                }
+                _ => return Err(invalid()),
+            }
+        }
+
+        rules.check()?;
+
+        Ok(rules)
+    }
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} target={} skunk={} double-skunk={} muggins={} pone-bonus={} partners={} reproducible={}",
+            self.name,
+            self.target,
+            self.skunk,
+            self.double_skunk,
+            self.muggins,
+            self.pone_bonus,
+            self.partners,
+            self.reproducible
+        )?;
+
+        for deal in &self.deals {
+            write!(
+                f,
+                " deal={}:{}:{}:{}",
+                deal.players, deal.dealt, deal.discard, deal.from_stock
+            )?;
+        }
+
+        Ok(())
+    }
+}
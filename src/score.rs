@@ -0,0 +1,119 @@
+// Allocation free hand scoring for bulk evaluation, gives the same totals as Hand::score
+use crate::cardset::CardSet;
+use crate::game::{Card, Rank, Suit};
+
+// Ways to choose k cards out of n, for n up to 4 cards of a rank
+const CHOOSE: [[u32; 5]; 5] = [
+    [1, 0, 0, 0, 0],
+    [1, 1, 0, 0, 0],
+    [1, 2, 1, 0, 0],
+    [1, 3, 3, 1, 0],
+    [1, 4, 6, 4, 1],
+];
+
+// Scores the hand for the 'Show' round with the given magic card
+pub fn score_hand(hand: CardSet, magic: &Card, crib: bool) -> u8 {
+    let mut full_hand = hand;
+    full_hand.insert(magic);
+
+    let counts = full_hand.rank_counts();
+
+    score_nob(hand, magic)
+        + score_fifteens(&counts)
+        + score_pairs(&counts)
+        + score_runs(&counts)
+        + score_flush(hand, magic, crib)
+}
+
+// Jack in hand matching the magic card's suit
+fn score_nob(hand: CardSet, magic: &Card) -> u8 {
+    let jack = Card {
+        rank: Rank::Jack,
+        suit: magic.suit,
+    };
+
+    if hand.contains(&jack) {
+        1
+    } else {
+        0
+    }
+}
+
+// Counts the subsets adding up to 15 one rank at a time
+fn score_fifteens(counts: &[u8; 13]) -> u8 {
+    let mut ways = [0u32; 16];
+    ways[0] = 1;
+
+    for (rank, &count) in Rank::ALL.iter().zip(counts) {
+        let value = rank.score_value() as usize;
+
+        // Going down so each rank only adds to sums made without it
+        for sum in (value..16).rev() {
+            for num in 1..=count as usize {
+                if num * value > sum {
+                    break;
+                }
+
+                ways[sum] += CHOOSE[count as usize][num] * ways[sum - num * value];
+            }
+        }
+    }
+
+    (ways[15] * 2) as u8
+}
+
+fn score_pairs(counts: &[u8; 13]) -> u8 {
+    counts
+        .iter()
+        .map(|count| count * count.saturating_sub(1))
+        .sum()
+}
+
+// Each maximal stretch of 3 or more ranks scores its length once for every way to pick it
+fn score_runs(counts: &[u8; 13]) -> u8 {
+    let mut points = 0;
+    let mut length = 0;
+    let mut ways = 1;
+
+    for &count in counts.iter().chain(&[0]) {
+        if count > 0 {
+            length += 1;
+            ways *= count;
+            continue;
+        }
+
+        if length >= 3 {
+            points += length * ways;
+        }
+
+        length = 0;
+        ways = 1;
+    }
+
+    points
+}
+
+// Hands score one a card without the magic card but the crib needs it too
+fn score_flush(hand: CardSet, magic: &Card, crib: bool) -> u8 {
+    let len = hand.len();
+
+    if len < 3 {
+        return 0;
+    }
+
+    let suit = match Suit::ALL
+        .into_iter()
+        .find(|suit| hand.suit_mask(*suit).count_ones() as usize == len)
+    {
+        Some(suit) => suit,
+        None => return 0,
+    };
+
+    if magic.suit == suit {
+        len as u8 + 1
+    } else if crib {
+        0
+    } else {
+        len as u8
+    }
+}
@@ -0,0 +1,95 @@
+// Shuffling that the players' secrets fully determine, on every platform and Rust version
+//
+// The secrets are joined one per line and hashed with SHA-256, the first 8 bytes of the hash
+// seed a SplitMix64 generator, and the deck is put in index order and then shuffled with
+// Fisher-Yates, swapping each card from the last down to the second with one at or below it.
+// Anyone with the same secrets gets the same deal. A reproducible server deals from a single
+// secret, the SHA-256 of its seed and the round, e.g. commitment("seed/1").
+use sha2::{Digest, Sha256};
+
+// SplitMix64 as published by Steele, Lea and Flood, small and fully specified
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> SplitMix64 {
+        SplitMix64 { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // Uniform in 0..bound, drawing again rather than favouring low numbers
+    pub fn below(&mut self, bound: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % bound;
+
+        loop {
+            let value = self.next_u64();
+
+            if value < zone {
+                return value % bound;
+            }
+        }
+    }
+}
+
+// Fisher-Yates shuffle driven by SplitMix64
+pub fn shuffle<T>(items: &mut [T], seed: u64) {
+    let mut rng = SplitMix64::new(seed);
+
+    for i in (1..items.len()).rev() {
+        let j = rng.below(i as u64 + 1) as usize;
+        items.swap(i, j);
+    }
+}
+
+// Every player picks a secret and sends its SHA-256 before anyone reveals theirs, so the deal
+// can't be steered by whoever goes last. The secrets are published after the hand and anyone
+// can check them against the commitments and rebuild the deck with Deck::shuffle_secrets.
+
+// 32 random hex digits, hex so secrets can be sent in a list
+pub fn new_secret() -> String {
+    hex(&rand::random::<[u8; 16]>())
+}
+
+pub fn is_valid_secret(secret: &str) -> bool {
+    !secret.is_empty() && secret.len() <= 64 && secret.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+// Commitments are always a full SHA-256, 64 hex digits
+pub fn is_valid_commitment(commit: &str) -> bool {
+    commit.len() == 64 && commit.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+// Hex SHA-256 of the secret
+pub fn commitment(secret: &str) -> String {
+    hex(&Sha256::digest(secret.as_bytes()))
+}
+
+// True if every revealed secret matches the commitment made for it
+pub fn verify_reveals(commits: &[String], reveals: &[String]) -> bool {
+    commits.len() == reveals.len()
+        && commits
+            .iter()
+            .zip(reveals)
+            .all(|(commit, reveal)| commitment(reveal) == *commit)
+}
+
+// First 8 bytes of the SHA-256 of the secrets in start order, one per line
+pub fn combine_secrets(secrets: &[String]) -> u64 {
+    let digest = Sha256::digest(secrets.join("\n").as_bytes());
+    let mut bytes = [0; 8];
+    bytes.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(bytes)
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
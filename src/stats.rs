@@ -0,0 +1,78 @@
+// How often every show score comes up over all 4 card hands and magic cards
+use crate::cardset::CardSet;
+use crate::game::Card;
+use crate::score::score_hand;
+use itertools::Itertools;
+
+// Highest score a hand or crib can make
+pub const MAX_SCORE: usize = 29;
+
+// Number of hand and magic card combinations, 270725 hands with 48 magic cards each
+pub const COMBINATIONS: u64 = 12_994_800;
+
+// Generated by crib_stats, the number of combinations giving each score from 0 to 29
+pub const HAND_FREQUENCIES: [u64; MAX_SCORE + 1] = [
+    1009008, 99792, 2813796, 505008, 2855676, 697508, 1800268, 751324, 1137236, 361224, 388740,
+    51680, 317340, 19656, 90100, 9168, 58248, 11196, 2708, 0, 8068, 2496, 444, 356, 3680, 0, 0, 0,
+    76, 4,
+];
+
+pub const CRIB_FREQUENCIES: [u64; MAX_SCORE + 1] = [
+    1022208, 99792, 2839800, 508908, 2868960, 703496, 1787176, 755320, 1118336, 358368, 378240,
+    43880, 310956, 16548, 88132, 9072, 57288, 11196, 2264, 0, 7828, 2472, 444, 356, 3680, 0, 0, 0,
+    76, 4,
+];
+
+// Counts every 4 card hand with every magic card, exhaustively
+pub fn frequencies(crib: bool) -> [u64; MAX_SCORE + 1] {
+    let deck: Vec<Card> = CardSet::FULL.iter().collect();
+    let mut counts = [0; MAX_SCORE + 1];
+
+    for cards in deck.iter().combinations(4) {
+        let hand: CardSet = cards.into_iter().collect();
+
+        for magic in hand.complement() {
+            counts[score_hand(hand, &magic, crib) as usize] += 1;
+        }
+    }
+
+    counts
+}
+
+pub fn table(crib: bool) -> &'static [u64; MAX_SCORE + 1] {
+    if crib {
+        &CRIB_FREQUENCIES
+    } else {
+        &HAND_FREQUENCIES
+    }
+}
+
+pub fn average(crib: bool) -> f64 {
+    average_of(table(crib))
+}
+
+// Average score over a table of frequencies, like one from frequencies()
+pub fn average_of(counts: &[u64]) -> f64 {
+    let total: u64 = counts
+        .iter()
+        .enumerate()
+        .map(|(score, count)| score as u64 * count)
+        .sum();
+
+    total as f64 / counts.iter().sum::<u64>() as f64
+}
+
+// Percent of all combinations a number of them make up
+pub fn percent(count: u64) -> f64 {
+    100.0 * count as f64 / COMBINATIONS as f64
+}
+
+// Percent of all combinations scoring the same or less
+pub fn percentile(score: u8, crib: bool) -> f64 {
+    percent(table(crib).iter().take(score as usize + 1).sum())
+}
+
+// Percent of all combinations scoring the same or more, e.g. 29 is in the top 0.00003%
+pub fn top_percent(score: u8, crib: bool) -> f64 {
+    percent(table(crib).iter().skip(score as usize).sum())
+}
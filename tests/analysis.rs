@@ -0,0 +1,117 @@
+// Checks the expected values behind the discard advice on hands with known answers
+use cribbage::analysis::{rank_discards, starter_stats, CribTable};
+use cribbage::game::{Card, Hand};
+use cribbage::CardSet;
+
+fn cards(names: &[&str]) -> CardSet {
+    names
+        .iter()
+        .map(|name| name.parse::<Card>().unwrap())
+        .collect()
+}
+
+#[test]
+fn starters_exclude_known_cards() {
+    let kept = cards(&["5H", "5C", "5S", "JD"]);
+    let discard = cards(&["2C", "9H"]);
+    let stats = starter_stats(kept, discard, false);
+
+    // Only the 5 of diamonds makes 29
+    assert_eq!(stats.starters(), 46);
+    assert_eq!(stats.max, 29);
+    assert_eq!(stats.distribution[29], 1);
+    assert_eq!(stats.min, 14);
+
+    let total: usize = stats
+        .distribution
+        .iter()
+        .enumerate()
+        .map(|(score, count)| score * count)
+        .sum();
+    assert_eq!(stats.expected, total as f64 / 46.0);
+
+    // Nothing known leaves 48 starters
+    assert_eq!(starter_stats(kept, CardSet::EMPTY, false).starters(), 48);
+}
+
+fn hand(names: &[&str]) -> Hand {
+    Hand::from(cards(names).iter().collect(), None)
+}
+
+#[test]
+fn discards_are_ranked_by_net() {
+    let dealt = hand(&["5H", "5C", "5S", "JD", "2C", "9H"]);
+    let mine = rank_discards(&dealt, 2, 0, true);
+    let theirs = rank_discards(&dealt, 2, 0, false);
+
+    assert_eq!(mine.len(), 15);
+    assert_eq!(theirs.len(), 15);
+
+    for options in [&mine, &theirs] {
+        assert!(options.windows(2).all(|pair| pair[0].net >= pair[1].net));
+
+        for option in options.iter() {
+            assert_eq!(option.discard.len(), 2);
+            assert_eq!(option.kept.len(), 4);
+        }
+    }
+
+    // The crib counts for the dealer and against the pone
+    for option in &mine {
+        let other = theirs
+            .iter()
+            .find(|other| other.discard == option.discard)
+            .unwrap();
+
+        assert_eq!(option.hand, other.hand);
+        assert_eq!(option.crib, other.crib);
+        assert_eq!(option.net, option.hand + option.crib);
+        assert_eq!(other.net, other.hand - other.crib);
+    }
+
+    assert_eq!(
+        mine[0].kept,
+        cards(&["5H", "5C", "5S", "JD"]).iter().collect::<Vec<_>>()
+    );
+
+    // Three or four players discard one from five
+    let dealt = hand(&["AH", "2C", "3S", "KD", "QD"]);
+    assert_eq!(rank_discards(&dealt, 1, 1, false).len(), 5);
+}
+
+#[test]
+fn crib_table_round_trips() {
+    let mut table = CribTable::new();
+
+    for (i, (a, b)) in CribTable::keys().into_iter().enumerate() {
+        table.set(&a, &b, true, i as f64 / 8.0);
+        table.set(&a, &b, false, -(i as f64) / 16.0);
+    }
+
+    let text = table.to_string();
+    let parsed: CribTable = text.parse().unwrap();
+
+    for (a, b) in CribTable::keys() {
+        assert_eq!(parsed.get(&a, &b, true), table.get(&a, &b, true));
+        assert_eq!(parsed.get(&a, &b, false), table.get(&a, &b, false));
+    }
+
+    assert_eq!(parsed.to_string(), text);
+
+    // Old tables are regenerated rather than misread
+    let old = text.replacen(
+        &format!("v{}", CribTable::VERSION),
+        &format!("v{}", CribTable::VERSION + 1),
+        1,
+    );
+    assert!(old.parse::<CribTable>().is_err());
+
+    // Every pair has to be there
+    let missing: String = text
+        .lines()
+        .filter(|line| !line.starts_with("A5s "))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    assert_ne!(missing, text);
+    assert!(missing.parse::<CribTable>().is_err());
+}
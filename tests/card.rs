@@ -0,0 +1,46 @@
+// Checks the card names players type and the server sends parse to the right cards
+use cribbage::game::{Card, ParseCardError, Rank, Suit};
+
+fn card(rank: Rank, suit: Suit) -> Card {
+    Card { rank, suit }
+}
+
+#[test]
+fn names_parse() {
+    assert_eq!("10h".parse(), Ok(card(Rank::Ten, Suit::Hearts)));
+    assert_eq!("TH".parse(), Ok(card(Rank::Ten, Suit::Hearts)));
+    assert_eq!("A♠".parse(), Ok(card(Rank::Ace, Suit::Spades)));
+    assert_eq!(" 5♧ ".parse(), Ok(card(Rank::Five, Suit::Clubs)));
+    assert_eq!("kd".parse(), Ok(card(Rank::King, Suit::Diamonds)));
+
+    // Every card comes back from its net name
+    for card in (0..52).flat_map(Card::from_index) {
+        assert_eq!(Card::from_net_name(&card.to_net_name()), Ok(card));
+    }
+}
+
+#[test]
+fn bad_names_fail() {
+    assert_eq!("".parse::<Card>(), Err(ParseCardError::Empty));
+    assert_eq!("  ".parse::<Card>(), Err(ParseCardError::Empty));
+    assert_eq!(
+        "h".parse::<Card>(),
+        Err(ParseCardError::Rank(String::new()))
+    );
+    assert_eq!(
+        "ZH".parse::<Card>(),
+        Err(ParseCardError::Rank(String::from("Z")))
+    );
+    assert_eq!(
+        "11H".parse::<Card>(),
+        Err(ParseCardError::Rank(String::from("11")))
+    );
+    assert_eq!(
+        "AX".parse::<Card>(),
+        Err(ParseCardError::Suit(String::from("X")))
+    );
+    assert_eq!(
+        "♠A".parse::<Card>(),
+        Err(ParseCardError::Rank(String::from("♠")))
+    );
+}
@@ -0,0 +1,90 @@
+// Checks the bit twiddling in CardSet against plain loops over the cards
+use cribbage::game::{Card, Rank, Suit};
+use cribbage::CardSet;
+
+fn card(name: &str) -> Card {
+    name.parse().unwrap()
+}
+
+#[test]
+fn rank_counts() {
+    assert_eq!(CardSet::EMPTY.rank_counts(), [0; 13]);
+    assert_eq!(CardSet::FULL.rank_counts(), [4; 13]);
+
+    let set: CardSet = ["AS", "AH", "AD", "5C", "KS", "KH"]
+        .iter()
+        .map(|name| card(name))
+        .collect();
+    let mut expected = [0; 13];
+    expected[0] = 3;
+    expected[4] = 1;
+    expected[12] = 2;
+
+    assert_eq!(set.rank_counts(), expected);
+
+    for rank in Rank::ALL {
+        let mut expected = [0; 13];
+        expected[rank.order() as usize - 1] = 4;
+        assert_eq!(CardSet::of_rank(rank).rank_counts(), expected);
+    }
+}
+
+#[test]
+fn suit_mask() {
+    for suit in Suit::ALL {
+        assert_eq!(CardSet::EMPTY.suit_mask(suit), 0);
+        assert_eq!(CardSet::FULL.suit_mask(suit), 0x1FFF);
+        assert_eq!(CardSet::of_suit(suit).suit_mask(suit), 0x1FFF);
+    }
+
+    let set: CardSet = ["AH", "5H", "KH", "5S"]
+        .iter()
+        .map(|name| card(name))
+        .collect();
+
+    assert_eq!(set.suit_mask(Suit::Hearts), 1 | 1 << 4 | 1 << 12);
+    assert_eq!(set.suit_mask(Suit::Spades), 1 << 4);
+    assert_eq!(set.suit_mask(Suit::Clubs), 0);
+}
+
+#[test]
+fn complement() {
+    assert_eq!(CardSet::EMPTY.complement(), CardSet::FULL);
+    assert_eq!(CardSet::FULL.complement(), CardSet::EMPTY);
+
+    let set: CardSet = ["AS", "10D", "KC"].iter().map(|name| card(name)).collect();
+    let rest = set.complement();
+
+    assert_eq!(rest.len(), 49);
+    assert!((set & rest).is_empty());
+    assert_eq!(set | rest, CardSet::FULL);
+    assert_eq!(rest.bits() >> 52, 0);
+}
+
+#[test]
+fn iter() {
+    assert_eq!(CardSet::EMPTY.iter().next(), None);
+    assert_eq!(CardSet::EMPTY.iter().len(), 0);
+
+    // Every card comes out once, in index order
+    let cards: Vec<Card> = CardSet::FULL.iter().collect();
+    let expected: Vec<Card> = (0..52).flat_map(Card::from_index).collect();
+
+    assert_eq!(CardSet::FULL.iter().len(), 52);
+    assert_eq!(cards, expected);
+
+    let set: CardSet = ["KC", "AS", "7H"].iter().map(|name| card(name)).collect();
+    let mut iter = set.iter();
+
+    assert_eq!(iter.len(), 3);
+    assert!(iter.next().is_some());
+    assert_eq!(iter.len(), 2);
+
+    let indices: Vec<u8> = set.iter().map(|card| card.index()).collect();
+    let mut sorted = indices.clone();
+    sorted.sort();
+
+    assert_eq!(indices, sorted);
+    assert_eq!(indices, set.indices().collect::<Vec<u8>>());
+    assert_eq!(set.iter().collect::<CardSet>(), set);
+}
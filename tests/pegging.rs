@@ -0,0 +1,136 @@
+// Plays out known sequences to check who scores the go, the last card and 31, and who leads next
+use cribbage::game::{Card, Hand, ScoreKind};
+use cribbage::pegging::{PlayError, PlayResult, Status};
+use cribbage::PeggingState;
+
+fn card(name: &str) -> Card {
+    name.parse().unwrap()
+}
+
+fn play(state: &mut PeggingState, name: &str) -> PlayResult {
+    state.play(Some(card(name))).unwrap()
+}
+
+fn kinds(result: &PlayResult) -> Vec<(ScoreKind, u8)> {
+    result
+        .breakdown
+        .events()
+        .iter()
+        .map(|event| (event.kind, event.points))
+        .collect()
+}
+
+#[test]
+fn go_scores_for_last_player_to_lay_a_card() {
+    let mut state = PeggingState::new(2, 0, 3);
+
+    play(&mut state, "KH");
+    play(&mut state, "QH");
+    play(&mut state, "JH");
+    assert_eq!(state.count(), 30);
+
+    // Player 1 can't play, player 0 still might
+    let result = state.play(None).unwrap();
+    assert!(!result.reset);
+    assert_eq!(state.status(1), Status::Go);
+    assert_eq!(state.current_player(), 0);
+
+    let result = state.play(None).unwrap();
+    assert!(result.reset);
+    assert_eq!(result.player, 0);
+    assert_eq!(kinds(&result), [(ScoreKind::Go, 1)]);
+
+    // Player after the last one to lay a card leads the new count
+    assert_eq!(state.count(), 0);
+    assert!(state.history().is_empty());
+    assert_eq!(state.current_player(), 1);
+    assert_eq!(state.status(0), Status::Playing);
+}
+
+#[test]
+fn thirty_one_scores_two_without_a_go() {
+    let mut state = PeggingState::new(2, 0, 3);
+
+    play(&mut state, "10H");
+    play(&mut state, "KH");
+    play(&mut state, "5S");
+
+    let result = play(&mut state, "6D");
+    assert!(result.reset);
+    assert_eq!(result.player, 1);
+    assert_eq!(kinds(&result), [(ScoreKind::ThirtyOne, 2)]);
+    assert_eq!(state.current_player(), 0);
+
+    // Last card of the play scores 1 for whoever laid it
+    play(&mut state, "AC");
+    let result = play(&mut state, "2C");
+    assert!(result.reset);
+    assert_eq!(result.player, 1);
+    assert_eq!(kinds(&result), [(ScoreKind::LastCard, 1)]);
+    assert!(state.is_finished());
+    assert_eq!(state.play(None).err(), Some(PlayError::Finished));
+}
+
+#[test]
+fn thirty_one_on_the_last_card() {
+    let mut state = PeggingState::new(2, 0, 2);
+
+    play(&mut state, "10H");
+    play(&mut state, "KH");
+    play(&mut state, "5S");
+
+    // No extra point for the last card when it makes 31
+    let result = play(&mut state, "6D");
+    assert!(state.is_finished());
+    assert_eq!(result.player, 1);
+    assert_eq!(kinds(&result), [(ScoreKind::ThirtyOne, 2)]);
+}
+
+#[test]
+fn players_out_of_cards_are_skipped() {
+    let mut state = PeggingState::new(3, 0, 2);
+
+    play(&mut state, "KH");
+    play(&mut state, "QH");
+    play(&mut state, "5C");
+    let result = play(&mut state, "6D");
+    assert_eq!(kinds(&result), [(ScoreKind::ThirtyOne, 2)]);
+    assert_eq!(state.status(0), Status::Out);
+    assert_eq!(state.current_player(), 1);
+
+    // Player 1 plays out and player 0 is skipped on the way round to player 2
+    play(&mut state, "AS");
+    assert_eq!(state.status(1), Status::Out);
+    assert_eq!(state.current_player(), 2);
+
+    let result = play(&mut state, "2S");
+    assert_eq!(result.player, 2);
+    assert_eq!(kinds(&result), [(ScoreKind::LastCard, 1)]);
+    assert!(state.is_finished());
+}
+
+#[test]
+fn validate_checks_the_hand_and_count() {
+    let mut state = PeggingState::new(2, 0, 4);
+
+    play(&mut state, "KH");
+    play(&mut state, "5S");
+    play(&mut state, "10C");
+    assert_eq!(state.count(), 25);
+
+    let hand = Hand::from(vec![card("9C"), card("6C")], None);
+    assert_eq!(state.legal_moves(&hand), [card("6C")]);
+    assert_eq!(state.validate(&hand, Some(&card("6C"))), Ok(()));
+    assert_eq!(
+        state.validate(&hand, Some(&card("AD"))),
+        Err(PlayError::NotInHand)
+    );
+    assert_eq!(
+        state.validate(&hand, Some(&card("9C"))),
+        Err(PlayError::OverCount)
+    );
+    assert_eq!(state.validate(&hand, None), Err(PlayError::CanPlay));
+
+    let stuck = Hand::from(vec![card("9C")], None);
+    assert_eq!(state.validate(&stuck, None), Ok(()));
+}
@@ -0,0 +1,112 @@
+// Checks rules survive the trip through the start frame and that changes apply to presets
+use cribbage::game::Skunk;
+use cribbage::rules::RuleSet;
+use cribbage::Deck;
+
+#[test]
+fn written_rules_parse_back() {
+    for name in RuleSet::PRESETS {
+        let rules = RuleSet::preset(name).unwrap();
+        assert_eq!(rules.to_string().parse::<RuleSet>(), Ok(rules));
+    }
+
+    let custom: RuleSet = "standard,muggins,target=61,skunk=31,double-skunk=0,deal=2:5:1"
+        .parse()
+        .unwrap();
+    assert_eq!(custom.to_string().parse::<RuleSet>(), Ok(custom));
+
+    let seeded: RuleSet = "five-card reproducible".parse().unwrap();
+    assert!(seeded.reproducible);
+    assert!(!RuleSet::standard().reproducible);
+    assert_eq!(seeded.to_string().parse::<RuleSet>(), Ok(seeded));
+}
+
+#[test]
+fn changes_apply_to_preset() {
+    let rules: RuleSet = "standard muggins target=91 skunk=61 double-skunk=31"
+        .parse()
+        .unwrap();
+
+    assert!(rules.muggins);
+    assert_eq!(rules.target, 91);
+    assert_eq!(rules.skunk(60), Skunk::Single);
+    assert_eq!(rules.skunk(30), Skunk::Double);
+    assert_eq!(rules.skunk(61), Skunk::None);
+    assert_eq!(rules.deal(2).unwrap().kept(), 4);
+
+    assert!("standard,target=0".parse::<RuleSet>().is_err());
+    assert!("standard,skunk=122".parse::<RuleSet>().is_err());
+    assert!("standard,deal=2:2:2".parse::<RuleSet>().is_err());
+    assert!("standard,deal=2:23:1".parse::<RuleSet>().is_err());
+    assert!("standard,deal=2:22:2".parse::<RuleSet>().is_ok());
+    assert!("standard,deal=18446744073709551615:2:1"
+        .parse::<RuleSet>()
+        .is_err());
+    assert!("standard,deal=2:5:1:18446744073709551615"
+        .parse::<RuleSet>()
+        .is_err());
+    assert!("nonsense".parse::<RuleSet>().is_err());
+}
+
+#[test]
+fn preset_deals_fill_the_crib() {
+    for name in RuleSet::PRESETS {
+        for deal in RuleSet::preset(name).unwrap().deals {
+            assert_eq!(
+                deal.crib_size(),
+                4,
+                "{} with {} players",
+                name,
+                deal.players
+            );
+        }
+    }
+
+    // The stock makes up whatever the discards leave short
+    let rules: RuleSet = "deal=3:5:1".parse().unwrap();
+    assert_eq!(rules.deal(3).unwrap().from_stock, 1);
+    assert_eq!(rules.deal(3).unwrap().crib_size(), 4);
+
+    assert!("deal=3:5:1:0".parse::<RuleSet>().is_err());
+    assert!("deal=2:6:3".parse::<RuleSet>().is_err());
+}
+
+#[test]
+fn partners_sit_opposite() {
+    let rules = RuleSet::standard();
+    let teams: Vec<usize> = (0..4).map(|player| rules.team(player, 4)).collect();
+
+    assert_eq!(teams, [0, 1, 0, 1]);
+    assert_eq!(rules.num_teams(4), 2);
+    assert_eq!(rules.team(2, 3), 2);
+
+    let alone: RuleSet = "standard,partners=false".parse().unwrap();
+    assert_eq!(rules.team_seats(3, 4), [1, 3]);
+    assert_eq!(rules.team_seats(0, 4), [0, 2]);
+    assert_eq!(rules.team_seats(2, 3), [2]);
+
+    assert_eq!(alone.team(2, 4), 2);
+    assert_eq!(alone.team_seats(2, 4), [2]);
+    assert_eq!(alone.num_teams(4), 4);
+}
+
+#[test]
+fn hands_go_out_from_the_first_player() {
+    let deal = *RuleSet::standard().deal(3).unwrap();
+
+    let mut deck = Deck::new();
+    deck.shuffle_seeded(7);
+    let (hands, stock) = deal.deal_hands(&mut deck, 1);
+
+    // Same deck dealt by hand, seat 1 then 2 then the dealer in seat 0
+    let mut expected = Deck::new();
+    expected.shuffle_seeded(7);
+    let order = [1, 2, 0].map(|seat| (seat, expected.deal(5)));
+
+    for (seat, hand) in order {
+        assert_eq!(hands[seat].cards(), hand.cards());
+    }
+
+    assert_eq!(stock.cards(), expected.deal(1).cards());
+    assert_eq!(deck.len(), 52 - 16);
+}
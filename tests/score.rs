@@ -0,0 +1,99 @@
+// Checks the fast scorer against Hand::score on every hand of 4 or 3 cards with every magic card
+use cribbage::game::{Card, Hand, ScoreKind};
+use cribbage::score::score_hand;
+use cribbage::CardSet;
+use itertools::Itertools;
+
+#[test]
+fn fast_scorer_matches_reference() {
+    let deck: Vec<Card> = (0..52).flat_map(Card::from_index).collect();
+
+    for cards in deck.iter().combinations(5) {
+        // Every card takes a turn as the magic card
+        for magic in cards.iter().copied() {
+            let kept: Vec<Card> = cards
+                .iter()
+                .filter(|card| **card != magic)
+                .cloned()
+                .cloned()
+                .collect();
+            let set = CardSet::from(kept.as_slice());
+
+            // Cribs only score differently when the hand could be a flush
+            let flush = kept.iter().map(|card| card.suit).all_equal();
+
+            for crib in [false, true].into_iter().filter(|crib| !crib || flush) {
+                let mut hand = Hand::from(kept.clone(), Some(magic.clone()));
+                hand.set_crib(crib);
+
+                assert_eq!(
+                    score_hand(set, magic, crib),
+                    hand.score().total(),
+                    "{} with magic {} (crib: {})",
+                    set,
+                    magic,
+                    crib
+                );
+            }
+        }
+    }
+}
+
+// Five card cribbage shows 3 card hands
+#[test]
+fn fast_scorer_matches_reference_for_three_cards() {
+    let deck: Vec<Card> = (0..52).flat_map(Card::from_index).collect();
+
+    for cards in deck.iter().combinations(4) {
+        for magic in cards.iter().copied() {
+            let kept: Vec<Card> = cards
+                .iter()
+                .filter(|card| **card != magic)
+                .cloned()
+                .cloned()
+                .collect();
+            let set = CardSet::from(kept.as_slice());
+
+            let hand = Hand::from(kept, Some(magic.clone()));
+
+            assert_eq!(
+                score_hand(set, magic, false),
+                hand.score().total(),
+                "{} with magic {}",
+                set,
+                magic
+            );
+        }
+    }
+}
+
+fn flush_points(cards: &[&str], magic: &str, crib: bool) -> u8 {
+    let cards = cards.iter().map(|name| name.parse().unwrap()).collect();
+    let mut hand = Hand::from(cards, Some(magic.parse().unwrap()));
+    hand.set_crib(crib);
+
+    hand.score()
+        .events()
+        .iter()
+        .filter(|event| event.kind == ScoreKind::Flush)
+        .map(|event| event.points)
+        .sum()
+}
+
+// Known answers, so a mistake shared by both scorers can't hide
+#[test]
+fn flush_rules() {
+    let hearts = ["2H", "4H", "8H", "KH"];
+
+    // A hand flushes on its own, a crib only with the magic card
+    assert_eq!(flush_points(&hearts, "9S", false), 4);
+    assert_eq!(flush_points(&hearts, "9S", true), 0);
+    assert_eq!(flush_points(&hearts, "9H", false), 5);
+    assert_eq!(flush_points(&hearts, "9H", true), 5);
+
+    // Five card cribbage shows 3 card hands
+    assert_eq!(flush_points(&["2H", "4H", "8H"], "9S", false), 3);
+    assert_eq!(flush_points(&["2H", "4H", "8H"], "9H", false), 4);
+
+    assert_eq!(flush_points(&["2H", "4H", "8H", "KS"], "9H", false), 0);
+}
@@ -0,0 +1,50 @@
+// Pins the shuffle so the same secrets deal the same cards on every platform and in every version
+use cribbage::shuffle::{combine_secrets, commitment, is_valid_commitment, shuffle, SplitMix64};
+use cribbage::Deck;
+
+#[test]
+fn hash_and_generator_match_published_values() {
+    // SHA-256 of "abc" starts ba7816bf8f01cfea
+    assert_eq!(commitment("abc")[..16], *"ba7816bf8f01cfea");
+    assert_eq!(
+        combine_secrets(&[String::from("abc")]),
+        0xba78_16bf_8f01_cfea
+    );
+
+    // First outputs of SplitMix64 seeded with 0
+    let mut rng = SplitMix64::new(0);
+    assert_eq!(rng.next_u64(), 0xe220_a839_7b1d_cdaf);
+    assert_eq!(rng.next_u64(), 0x6e78_9e6a_a1b9_65f4);
+    assert_eq!(rng.next_u64(), 0x06c4_5d18_8009_454f);
+}
+
+#[test]
+fn seed_deals_known_cards() {
+    let mut items: Vec<u8> = (0..10).collect();
+    shuffle(&mut items, 1);
+    assert_eq!(items, [4, 2, 8, 1, 9, 3, 0, 6, 7, 5]);
+
+    // First round of a server started with --reproducible seed
+    let mut deck = Deck::new();
+    let effective = deck.shuffle_secrets(&[commitment("seed/1")]);
+    assert_eq!(effective, 0x38a9_0e39_e8ef_021a);
+
+    let mut names = |count| {
+        deck.deal(count)
+            .cards()
+            .iter()
+            .map(|card| card.to_net_name())
+            .collect::<Vec<String>>()
+    };
+
+    assert_eq!(names(6), ["3S", "7H", "3C", "9D", "KD", "8H"]);
+    assert_eq!(names(6), ["6S", "3H", "7D", "8D", "5C", "8C"]);
+}
+
+#[test]
+fn commitments_are_full_hashes() {
+    assert!(is_valid_commitment(&commitment("c0ffee")));
+    assert!(!is_valid_commitment("c0ffee"));
+    assert!(!is_valid_commitment(&"g".repeat(64)));
+    assert!(!is_valid_commitment(&"a".repeat(65)));
+}
@@ -0,0 +1,10 @@
+// Checks the embedded frequency tables against a full count of every hand
+use cribbage::stats::{self, COMBINATIONS, CRIB_FREQUENCIES, HAND_FREQUENCIES};
+
+#[test]
+fn embedded_tables_match_full_count() {
+    assert_eq!(stats::frequencies(false), HAND_FREQUENCIES);
+    assert_eq!(stats::frequencies(true), CRIB_FREQUENCIES);
+    assert_eq!(HAND_FREQUENCIES.iter().sum::<u64>(), COMBINATIONS);
+    assert_eq!(CRIB_FREQUENCIES.iter().sum::<u64>(), COMBINATIONS);
+}